@@ -1,4 +1,8 @@
+use bevy::ecs::entity::{EntityMapper, MapEntities};
 use bevy::prelude::*;
+use std::collections::HashMap;
+use crate::constants::*;
+use crate::resources::{technology::Technology, genealogy::Genealogy};
 
 #[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Position {
@@ -6,65 +10,208 @@ pub struct Position {
     pub y: i32,
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Clone, Debug)]
 pub struct Calories {
     pub current: i32,
     pub max: i32,
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Clone, Debug)]
+pub struct Hydration {
+    pub current: i32,
+    pub max: i32,
+}
+
+#[derive(Component, Clone, Debug)]
+pub struct Rest {
+    pub current: i32,
+    pub max: i32,
+}
+
+#[derive(Component, Clone, Debug)]
+pub struct BodyWarmth {
+    pub current: i32,
+    pub max: i32,
+}
+
+#[derive(Component, Clone, Debug)]
 pub struct FoodSource {
     pub nutrition_value: i32,
 }
 
 // --- Intent Components ---
 
-#[derive(Component, Debug)]
+#[derive(Component, Clone, Debug)]
 pub struct WantsToEat;
 
-#[derive(Component, Debug)]
+#[derive(Component, Clone, Debug)]
+pub struct WantsToDrink;
+
+#[derive(Component, Clone, Debug)]
+pub struct WantsToSleep;
+
+#[derive(Component, Clone, Debug)]
+pub struct WantsToWarm;
+
+#[derive(Component, Clone, Debug)]
 pub struct WantsToIdle;
 
-#[derive(Component, Debug)]
+#[derive(Component, Clone, Debug)]
 pub struct WantsToProcreate;
 
-#[derive(Component, Debug)]
+#[derive(Component, Clone, Debug)]
 pub struct WantsToReturnToBand;
 
+// An order from the player; it takes priority over the creature's own goals until carried out
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub enum PlayerOrder {
+    MoveTo(Position),
+    Eat(#[entities] Entity),
+    BuildRaft(Position), // Shore tile to build the raft at
+    ReturnHome,
+}
+
 // --- Action Components ---
 
-#[derive(Component, Debug)]
+#[derive(Component, Clone, Debug)]
 pub struct ActionTravelTo {
     pub destination: Position,
+    #[entities]
+    pub target: Option<Entity>, // Entity being followed; destination tracks its position
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Clone, Debug)]
 pub struct ActionEat {
+    #[entities]
     pub target_entity: Entity,
     pub progress: u32,
     pub max_progress: u32,
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Clone, Debug)]
+pub struct ActionDrink {
+    pub source: Position, // Fresh water tile being drunk from
+    pub progress: u32,
+    pub max_progress: u32,
+}
+
+#[derive(Component, Clone, Debug)]
+pub struct ActionSleep;
+
+#[derive(Component, Clone, Debug)]
+pub struct ActionWarm {
+    pub progress: u32,
+    pub max_progress: u32,
+}
+
+#[derive(Component, Clone, Debug)]
 pub struct ActivePath {
     pub nodes: Vec<Position>,
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Clone, Debug)]
 pub struct OutsideBandRadius;
 
+// Scores from the creature's most recent goal selection, kept for debugging
+#[derive(Component, Clone, Debug, Default)]
+pub struct UtilityScores(pub Vec<(IntentKind, f32)>);
+
+// --- Items ---
+
+// A raft the creature carries, letting it travel over any water
+#[derive(Component, Clone, Debug)]
+pub struct Raft;
+
+// --- Knowledge ---
+
+// What a creature last saw of a patch of land
+#[derive(Clone, Copy, Debug)]
+pub struct FoodSighting {
+    pub tick: u32,
+    pub has_food: bool,
+}
+
+// Patches of land (FOOD_PATCH_SIZE tiles square) a creature has looked over, keyed by patch coordinates
+#[derive(Component, Debug, Default, Clone)]
+pub struct FoodMemory(pub HashMap<Position, FoodSighting>);
+
+impl FoodMemory {
+    pub fn patch_of(pos: Position) -> Position {
+        Position { x: pos.x.div_euclid(FOOD_PATCH_SIZE), y: pos.y.div_euclid(FOOD_PATCH_SIZE) }
+    }
+
+    pub fn patch_center(patch: Position) -> Position {
+        Position { x: patch.x * FOOD_PATCH_SIZE + FOOD_PATCH_SIZE / 2, y: patch.y * FOOD_PATCH_SIZE + FOOD_PATCH_SIZE / 2 }
+    }
+
+    // Keeps whichever sighting of the patch is newer
+    pub fn record(&mut self, patch: Position, sighting: FoodSighting) {
+        let entry = self.0.entry(patch).or_insert(sighting);
+        if sighting.tick >= entry.tick {
+            *entry = sighting;
+        }
+    }
+
+    pub fn merge(&mut self, other: &FoodMemory) {
+        for (&patch, &sighting) in &other.0 {
+            self.record(patch, sighting);
+        }
+    }
+
+    pub fn forget_before(&mut self, tick: u32) {
+        self.0.retain(|_, sighting| sighting.tick >= tick);
+    }
+
+    // Center of the closest remembered patch with food that is out of sight from pos; ties go to
+    // the lowest coordinates so the choice doesn't depend on HashMap order
+    pub fn nearest_food(&self, pos: Position) -> Option<Position> {
+        self.0.iter()
+            .filter(|(_, sighting)| sighting.has_food)
+            .map(|(&patch, _)| Self::patch_center(patch))
+            .filter(|center| (center.x - pos.x).abs().max((center.y - pos.y).abs()) > VISION_RADIUS)
+            .min_by_key(|center| ((center.x - pos.x).abs().max((center.y - pos.y).abs()), center.x, center.y))
+    }
+}
+
+// --- Family ---
+
+// Number given to each creature in order of birth; unlike its Entity, it comes out the same
+// every time a run is re-simulated, so replays refer to creatures by it
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CreatureId(pub u32);
+
+// Where a creature comes from; the founders the world started with are their own founder
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Lineage {
+    #[entities]
+    pub mother: Option<Entity>,
+    #[entities]
+    pub founder: Entity,
+    pub generation: u32,
+}
+
+impl Lineage {
+    pub fn founder(entity: Entity) -> Self {
+        Self { mother: None, founder: entity, generation: 0 }
+    }
+
+    pub fn child_of(mother: Entity, mother_lineage: &Lineage) -> Self {
+        Self { mother: Some(mother), founder: mother_lineage.founder, generation: mother_lineage.generation + 1 }
+    }
+}
+
 // --- Markers ---
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct CreatureMarker;
 
-#[derive(Component, Debug)]
+#[derive(Component, Clone, Debug)]
 pub struct Pregnant {
     pub progress: u32,
     pub max_progress: u32,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct PlantMarker {
     pub plant_type: PlantType,
 }
@@ -72,31 +219,45 @@ pub struct PlantMarker {
 #[derive(Component)]
 pub struct TileMarker;
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct Edible;
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct Harvestable;
 
-#[derive(Component, Debug)]
+// Query filter for plants a creature can eat right now
+pub type EdiblePlant = (With<PlantMarker>, With<Harvestable>, With<Edible>);
+
+#[derive(Component, Clone, Debug)]
 pub struct BeingConsumed {
+    #[entities]
     pub consumer_entity: Entity,
 }
 
 #[derive(Component)]
 pub struct TickText;
 
+#[derive(Component)]
+pub struct DateText;
+
 #[derive(Component)]
 pub struct PopulationText;
 
 #[derive(Component)]
-pub struct BandCenterMarker;
+pub struct SpeedText;
 
 #[derive(Component)]
-pub struct BandCircleMarker;
+pub struct NightOverlay;
 
+// Rectangle drawn while dragging out a box selection
 #[derive(Component)]
-pub struct PathVisualizationEnabled;
+pub struct SelectionBox;
+
+#[derive(Component)]
+pub struct BandCenterMarker;
+
+#[derive(Component)]
+pub struct BandCircleMarker;
 
 #[derive(Component, Debug)]
 pub struct PathMarker {
@@ -107,6 +268,41 @@ pub struct PathMarker {
 #[derive(Component)]
 pub struct SelectedPanelRoot;
 
+// Which part of the left panel a UI node belongs to; only the part for the current LeftPanelState is shown
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanelSection {
+    Creature,
+    Plant,
+    Tile,
+    Band,
+}
+
+// A line of the plant, tile or band inspector
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InspectorLine {
+    PlantType,
+    PlantNutrition,
+    PlantStatus,
+    TileKind,
+    TileMoveCost,
+    TileFertility,
+    TileElevation,
+    BandCenter,
+    BandMembers,
+    BandMode,
+    BandLineages,
+}
+
+impl InspectorLine {
+    pub fn section(&self) -> PanelSection {
+        match self {
+            Self::PlantType | Self::PlantNutrition | Self::PlantStatus => PanelSection::Plant,
+            Self::TileKind | Self::TileMoveCost | Self::TileFertility | Self::TileElevation => PanelSection::Tile,
+            Self::BandCenter | Self::BandMembers | Self::BandMode | Self::BandLineages => PanelSection::Band,
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct SelectedEntityIdText;
 
@@ -116,14 +312,237 @@ pub struct SelectedCaloriesText;
 #[derive(Component)]
 pub struct SelectedPregnancyText;
 
+#[derive(Component)]
+pub struct SelectedUtilityText;
+
+#[derive(Component)]
+pub struct SelectedHydrationText;
+
+#[derive(Component)]
+pub struct SelectedRestText;
+
+#[derive(Component)]
+pub struct SelectedWarmthText;
+
+#[derive(Component)]
+pub struct SelectedFamilyTreeText;
+
+// --- UI: Charts Panel Markers ---
+#[derive(Component)]
+pub struct ChartsPanelRoot;
+
+// One coloured entry of a chart's legend, showing the series' current value
+#[derive(Component, Debug)]
+pub struct ChartLegend {
+    pub chart: ChartKind,
+    pub series: usize,
+}
+
+#[derive(Component, Debug)]
+pub struct ChartWindowButton(pub crate::resources::ui_elements::ChartWindow);
+
+// --- UI: Event Log Markers ---
+#[derive(Component)]
+pub struct EventLogPanelRoot;
+
+#[derive(Component)]
+pub struct EventLogText;
+
+// --- UI: Replay Timeline Markers ---
+// Bar spanning the whole recording during playback; clicking it seeks to that point
+#[derive(Component)]
+pub struct ReplayTimeline;
+
+#[derive(Component)]
+pub struct ReplayTimelineFill;
+
 // --- Enums ---
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PlantType {
     Wheat
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IntentKind {
+    ReturnToBand,
+    Drink,
+    Warm,
+    Eat,
+    Sleep,
+    Procreate,
+    Idle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DisasterKind {
+    Flood,
+    Wildfire,
+    HarshWinter,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeathCause {
+    Starvation,
+    Thirst,
+    Cold,
+    Drowning,
+}
+
+impl DeathCause {
+    pub const ALL: [DeathCause; 4] = [Self::Starvation, Self::Thirst, Self::Cold, Self::Drowning];
+
+    // Position in ALL, for per-cause tallies
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Starvation => "Starvation",
+            Self::Thirst => "Thirst",
+            Self::Cold => "Cold",
+            Self::Drowning => "Drowning",
+        }
+    }
+}
+
+// Which time series a chart in the charts panel plots
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartKind {
+    Population,
+    BirthsAndDeaths,
+    Plants,
+    Calories,
+}
+
+impl ChartKind {
+    pub const ALL: [ChartKind; 4] = [Self::Population, Self::BirthsAndDeaths, Self::Plants, Self::Calories];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::Population => "Population",
+            Self::BirthsAndDeaths => "Births and deaths",
+            Self::Plants => "Plants",
+            Self::Calories => "Average calories",
+        }
+    }
+}
+
 // --- Events ---
 #[derive(Event, Debug)]
 pub struct FoodTargetInvalidated {
     pub creature_entity: Entity,
 }
+
+// A GameGrid tile changed kind after world generation
+#[derive(Event, Debug)]
+pub struct TileChanged {
+    pub position: Position,
+}
+
+// A tile's surface changed what it costs to cross it, but not whether it can be entered, so
+// routes through it stay usable and only cached search costs go stale
+#[derive(Event, Debug)]
+pub struct TileCostChanged {
+    pub position: Position,
+}
+
+// Something worth recording happened in the simulation; the event log stamps each with its tick
+#[derive(Event, Debug, Clone, Copy)]
+pub enum SimEvent {
+    Birth { child: Entity, mother: Entity, position: Position },
+    Death { entity: Entity, cause: DeathCause, position: Position },
+    PlantEaten { creature: Entity, position: Position },
+    PathFailed { creature: Entity, from: Position, to: Position },
+    BandMigrated { from: Position, to: Position },  // The band moved its own center
+    BandCenterSet { from: Position, to: Position }, // The player moved the band center
+    Disaster { kind: DisasterKind, position: Option<Position> },
+    TechnologyDiscovered(Technology),
+}
+
+// Keeps the event log naming the right creatures when a replay snapshot is restored
+impl MapEntities for SimEvent {
+    fn map_entities<M: EntityMapper>(&mut self, mapper: &mut M) {
+        match self {
+            Self::Birth { child, mother, .. } => {
+                child.map_entities(mapper);
+                mother.map_entities(mapper);
+            }
+            Self::Death { entity, .. } => entity.map_entities(mapper),
+            Self::PlantEaten { creature, .. } | Self::PathFailed { creature, .. } => creature.map_entities(mapper),
+            Self::BandMigrated { .. } | Self::BandCenterSet { .. } | Self::Disaster { .. } | Self::TechnologyDiscovered(_) => {}
+        }
+    }
+}
+
+impl SimEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Birth { .. } => "Birth",
+            Self::Death { .. } => "Death",
+            Self::PlantEaten { .. } => "PlantEaten",
+            Self::PathFailed { .. } => "PathFailed",
+            Self::BandMigrated { .. } => "BandMigrated",
+            Self::BandCenterSet { .. } => "BandCenterSet",
+            Self::Disaster { .. } => "Disaster",
+            Self::TechnologyDiscovered(_) => "TechnologyDiscovered",
+        }
+    }
+
+    // One line for the in-game log, creatures named by their CreatureId
+    pub fn describe(&self, genealogy: &Genealogy) -> String {
+        let name = |entity: &Entity| genealogy.name(*entity);
+        match self {
+            Self::Birth { child, mother, position } => format!("{} born to {} at ({}, {})", name(child), name(mother), position.x, position.y),
+            Self::Death { entity, cause, position } => format!("{} died of {} at ({}, {})", name(entity), cause.label().to_ascii_lowercase(), position.x, position.y),
+            Self::PlantEaten { creature, position } => format!("{} ate a plant at ({}, {})", name(creature), position.x, position.y),
+            Self::PathFailed { creature, from, to } => format!("{} found no path from ({}, {}) to ({}, {})", name(creature), from.x, from.y, to.x, to.y),
+            Self::BandMigrated { from, to } => format!("Band migrated from ({}, {}) to ({}, {})", from.x, from.y, to.x, to.y),
+            Self::BandCenterSet { from, to } => format!("Band center moved by hand from ({}, {}) to ({}, {})", from.x, from.y, to.x, to.y),
+            Self::Disaster { kind, position: Some(position) } => format!("{:?} at ({}, {})", kind, position.x, position.y),
+            Self::Disaster { kind, position: None } => format!("{:?}", kind),
+            Self::TechnologyDiscovered(technology) => format!("Discovered {:?}", technology),
+        }
+    }
+
+    // The event's fields as JSON key/value pairs, values already encoded; creatures are given by
+    // their CreatureId, or null for an entity the genealogy doesn't know
+    pub fn json_fields(&self, genealogy: &Genealogy) -> Vec<(&'static str, String)> {
+        let entity = |entity: &Entity| genealogy.id_of(*entity).map_or("null".to_string(), |id| id.0.to_string());
+        let text = |value: String| format!("\"{}\"", value);
+        let mut fields = Vec::new();
+        let mut position = |prefix: &'static str, pos: &Position| {
+            fields.push((prefix, format!("[{},{}]", pos.x, pos.y)));
+        };
+        match self {
+            Self::Birth { position: pos, .. } | Self::Death { position: pos, .. } | Self::PlantEaten { position: pos, .. } => position("position", pos),
+            Self::PathFailed { from, to, .. } | Self::BandMigrated { from, to } | Self::BandCenterSet { from, to } => {
+                position("from", from);
+                position("to", to);
+            }
+            Self::Disaster { position: Some(pos), .. } => position("position", pos),
+            Self::Disaster { position: None, .. } | Self::TechnologyDiscovered(_) => {}
+        }
+        match self {
+            Self::Birth { child, mother, .. } => {
+                fields.push(("child", entity(child)));
+                fields.push(("mother", entity(mother)));
+            }
+            Self::Death { entity: dead, cause, .. } => {
+                fields.push(("entity", entity(dead)));
+                fields.push(("cause", text(cause.label().to_string())));
+            }
+            Self::PlantEaten { creature, .. } | Self::PathFailed { creature, .. } => fields.push(("creature", entity(creature))),
+            Self::BandMigrated { .. } | Self::BandCenterSet { .. } => {}
+            Self::Disaster { kind, .. } => fields.push(("kind", text(format!("{:?}", kind)))),
+            Self::TechnologyDiscovered(technology) => fields.push(("technology", text(format!("{:?}", technology)))),
+        }
+        fields
+    }
+}
+
+#[derive(Event, Debug)]
+pub struct DisasterOccurred {
+    pub kind: DisasterKind,
+    pub position: Option<Position>,
+}
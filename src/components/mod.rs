@@ -1 +1,2 @@
+#[allow(clippy::module_inception)]
 pub mod components;
\ No newline at end of file
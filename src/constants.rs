@@ -5,6 +5,7 @@ pub const GRID_WIDTH: usize = 700;
 pub const GRID_HEIGHT: usize = 400;
 pub const TILE_SIZE: f32 = 32.0;
 pub const TICK_RATE_HZ: f64 = 2.0;
+pub const MAX_TICK_RATE_HZ: f64 = 1000.0; // Tick rate asked for at max speed; frames slow down to keep up
 
 // --- Window/Camera Constants ---
 pub const DEFAULT_WINDOW_WIDTH: f32 = 1200.0;
@@ -13,12 +14,36 @@ pub const DEFAULT_ZOOM: f32 = 1.0;
 pub const MIN_ZOOM: f32 = 0.1;
 pub const ZOOM_SPEED: f32 = 0.1;
 pub const CAMERA_PAN_SPEED: f32 = 400.0;
+pub const DRAG_SELECT_THRESHOLD: f32 = 8.0; // Screen pixels the cursor must move for a click to become a box selection
 
 // --- World Constants ---
 pub const WATER_LEVEL: f32 = 0.3; // Tiles below this are lakes
 pub const SCALE: f64 = 0.02;      // Controls how zoomed in/out the noise is
 pub const WHEAT_SCALE: f64 = 0.07; // Controls wheat patch size - smaller = larger patches
 pub const WHEAT_THRESHOLD: f32 = 0.7; // Noise value above which wheat spawns
+pub const CONTINENT_NOISE_WEIGHT: f32 = 0.4; // How much noise roughens continent coastlines
+pub const ARCHIPELAGO_ISLAND_COUNT: usize = 40;
+pub const DESERT_MOVE_COST: i32 = 3;
+pub const MOUNTAIN_MOVE_COST: i32 = 40;
+pub const MOUNTAIN_ELEVATION: f32 = 0.8; // Land at least this high is mountainous
+pub const PEAK_ELEVATION: f32 = 0.9; // Land at least this high is impassable
+pub const OFF_TRAIL_COST: u32 = 2; // Extra step cost on land no one has worn a trail into
+pub const TRAIL_COST: u32 = 1;
+pub const TRAIL_TRAFFIC: f32 = 30.0; // Footsteps that wear a tile into a trail
+pub const ROAD_TRAFFIC: f32 = 150.0;
+pub const TRAFFIC_DECAY: f32 = 0.9; // Fraction of footsteps remembered from one day to the next
+pub const COAST_RISE: f32 = 0.1; // Continent height over which preset land climbs from sea level to full relief
+pub const UPHILL_COST_FACTOR: f32 = 200.0; // Extra step cost per unit of elevation climbed
+pub const DOWNHILL_COST_FACTOR: f32 = 50.0;
+pub const RIVER_COUNT: usize = 60;
+pub const RIVER_SOURCE_HEIGHT: f32 = 0.7; // Rivers only start above this height
+pub const RIVER_MAX_LENGTH: usize = 400;
+pub const RIVER_MOVE_COST: i32 = 5;
+pub const RIVER_BASIN_LAKE_RADIUS: usize = 2;
+pub const FLOODPLAIN_RADIUS: usize = 2;
+pub const SHALLOW_WATER_RADIUS: i32 = 2; // Ocean within this many tiles of land is shallow
+pub const SWIM_MOVE_COST: i32 = 50;
+pub const BOAT_MOVE_COST: u32 = 5;
 
 // --- Creature Constants ---
 pub const MOVE_COST: i32 = 300;
@@ -27,13 +52,114 @@ pub const WORK_COST: i32 = 20;
 pub const PREGNANT_COST: i32 = 10000;
 pub const HUMAN_MAX_CALORIES: i32 = 25000;
 pub const HUMAN_PREGNANCY_DURATION: u32 = 200;
+pub const HUMAN_MAX_HYDRATION: i32 = 1000;
+pub const THIRST_COST: i32 = 2;
+pub const DRINK_DURATION: u32 = 2;
+pub const WATER_SEARCH_RADIUS: i32 = 100;
+pub const HUMAN_MAX_REST: i32 = 1000;
+pub const FATIGUE_COST: i32 = 1;
+pub const SLEEP_RECOVERY: i32 = 10;
+pub const EXHAUSTION_COST: i32 = 100; // Extra calorie burn per tick when rest is depleted
+pub const HUMAN_MAX_WARMTH: i32 = 1000;
+pub const COMFORT_TEMPERATURE: f32 = 15.0; // Below this creatures lose body warmth
+pub const COLD_EXPOSURE_RATE: f32 = 0.3; // Warmth lost per tick per degree below comfort
+pub const WARMTH_RECOVERY: i32 = 5;
+pub const HUDDLE_WARMTH_BONUS: i32 = 3; // Extra warmth per adjacent creature while huddling
+pub const WARM_DURATION: u32 = 20;
 pub const BAND_RADIUS: i32 = 10;
+pub const BAND_FORAGE_RADIUS: i32 = 20;
+pub const FOOD_SCARCITY_THRESHOLD: usize = 10; // Fewer edible plants than this in forage range triggers migration
+pub const MIGRATION_DISTANCE: i32 = 40;
+pub const VISION_RADIUS: i32 = 8; // How far a creature can see food
+pub const FOOD_PATCH_SIZE: i32 = 5; // Side length of the patches of land creatures remember food in
+pub const FOOD_MEMORY_DAYS: u32 = 10; // How long a sighting is remembered
+pub const EXPLORE_DISTANCE: i32 = 15; // How far a hungry creature with no leads wanders to look for food
+pub const SWIM_COST: i32 = 600; // Extra calories burned per tick in the water
+pub const DROWNING_CHANCE: f32 = 0.02; // Per tick spent swimming in deep water
+pub const RAFT_BUILD_COST: i32 = 2000;
+pub const TRAIL_CALORIE_MULTIPLIER: f32 = 0.75; // Scales MOVE_COST on trails
+pub const ROAD_CALORIE_MULTIPLIER: f32 = 0.5;
+pub const UTILITY_CONFIG_PATH: &str = "assets/utility.txt"; // Intent scorers read at startup unless --utility-config names another file
+
+// --- Calendar Constants ---
+pub const TICKS_PER_DAY: u32 = 24;
+pub const DAYS_PER_SEASON: u32 = 15;
+pub const SUMMER_TEMPERATURE_OFFSET: f32 = 5.0;
+pub const WINTER_TEMPERATURE_OFFSET: f32 = -12.0;
+pub const WINTER_CALORIE_BURN_MULTIPLIER: f32 = 1.5;
+pub const NIGHT_START_HOUR: u32 = 20;
+pub const NIGHT_END_HOUR: u32 = 6;
+pub const FIRE_EXTRA_HOURS: u32 = 2; // Hours of evening activity firelight adds
+pub const NIGHT_OVERLAY_ALPHA: f32 = 0.55;
+pub const STATS_INTERVAL_TICKS: u32 = TICKS_PER_DAY; // Default ticks between rows of the stats file
+pub const REPLAY_KEYFRAME_INTERVAL: u32 = TICKS_PER_DAY; // Ticks between world checksums in a replay file
+pub const REPLAY_SEEK_TICKS: u32 = TICKS_PER_DAY * DAYS_PER_SEASON; // How far [ and ] seek during playback
+pub const REPLAY_TIMELINE_WIDTH: f32 = 300.0; // Pixels; the timeline spans the whole recording
+pub const REPLAY_MAX_SNAPSHOTS: usize = 16; // World snapshots kept for seeking, each around 10 MB
+
+// --- Technology Constants ---
+pub const FIRE_DISCOVERY_CHANCE: f32 = 0.002;
+pub const RAFT_DISCOVERY_CHANCE: f32 = 0.0005;
+
+// --- Weather Constants ---
+pub const WEATHER_MIN_DAYS: u32 = 2;
+pub const WEATHER_MAX_DAYS: u32 = 6;
+pub const RAIN_GROWTH_MULTIPLIER: f32 = 2.0;
+pub const FLOOD_CHANCE: f32 = 0.15; // Per rainy day
+pub const FLOOD_RADIUS: i32 = 6;
+pub const FLOOD_DURATION_DAYS: u32 = 3;
+pub const WILDFIRE_CHANCE: f32 = 0.1; // Per drought day
+pub const WILDFIRE_RADIUS: i32 = 8;
+pub const HARSH_WINTER_CHANCE: f32 = 0.25;
+pub const HARSH_WINTER_TEMPERATURE_DROP: f32 = 8.0;
+pub const HARSH_WINTER_BURN_MULTIPLIER: f32 = 1.3;
+
+// --- Climate Constants ---
+pub const EQUATOR_TEMPERATURE: f32 = 30.0;
+pub const POLE_TEMPERATURE: f32 = -15.0;
+pub const MOUNTAIN_TEMPERATURE_DROP: f32 = 10.0;
+pub const ELEVATION_TEMPERATURE_DROP: f32 = 20.0; // Degrees colder at the highest elevation than at sea level
+
+// --- Pathfinding Constants ---
+pub const NAV_CLUSTER_SIZE: i32 = 20; // Side length of the square clusters the abstract graph is built over
+pub const NAV_ENTRANCE_SPLIT_LENGTH: usize = 6; // Entrances at least this wide get a transition at each end
+pub const NAV_WATER_COST: u32 = 100; // Step costs at or above this count as open water when placing entrances
+pub const NAV_SEARCH_MARGIN: i32 = 5; // Tiles a short trip's search may stray outside the clusters of its ends
+pub const NAV_FALLBACK_MAX_NODES: usize = 20_000; // Tiles a direct search may expand when the abstract graph finds no route
+pub const MAX_PATH_REQUESTS_PER_TICK: usize = 64; // New routes and detours together; flow field lookups don't count toward this
+pub const FLOW_FIELD_RADIUS: i32 = 80;
+pub const FLOW_FIELD_REBUILD_DISTANCE: i32 = 3; // How far the band center may drift before its flow field is rebuilt
+pub const NEAREST_ENTERABLE_RADIUS: i32 = 10; // How far an unenterable destination may be moved to solid ground
+pub const TARGET_REPLAN_DISTANCE: i32 = 2; // How far a followed target may move from the path's end before replanning
 
 // --- Plant Constants ---
 pub const WHEAT_NUTRIENTS: i32 = 5000;
+pub const PLANT_GROWTH_CHANCE: u32 = 2000; // Each plant spreads with 1-in-N odds per spring tick
+pub const DIRT_FERTILITY: f32 = 0.6;
+pub const FLOODPLAIN_FERTILITY: f32 = 1.0;
+pub const DESERT_FERTILITY: f32 = 0.1;
+pub const MOUNTAIN_FERTILITY: f32 = 0.15;
+pub const WHEAT_FERTILITY_BONUS: f32 = 0.25; // How far each unit of fertility above dirt lowers WHEAT_THRESHOLD
+pub const MIN_GROWTH_FERTILITY: f32 = 0.2; // Plants won't spread onto poorer soil than this
+pub const PLANT_GROWTH_FERTILITY_COST: f32 = 0.05; // Drawn from the soil a new plant takes root in
+pub const HARVEST_FERTILITY_COST: f32 = 0.1; // Drawn from the soil each time a plant is eaten
+pub const FALLOW_RECOVERY: f32 = 0.01; // Fertility regained per day by soil with nothing growing on it
 
 
 // --- Visual Constants ---
+pub const CONTOUR_INTERVAL: f32 = 0.05; // Elevation between contour lines in the elevation render mode
+pub const CONTOUR_LINE_WIDTH: f32 = 0.15; // Fraction of each interval drawn as the line
+pub const FERTILITY_LEVELS: f32 = 10.0; // Steps of soil fertility the terrain colors distinguish
+pub const FOG_EXPLORED_DARKNESS: f32 = 0.6; // How far explored tiles out of sight are darkened
+pub const CHART_WIDTH: u32 = 300; // Pixels; also the most points a chart plots
+pub const CHART_HEIGHT: u32 = 80;
+pub const CHART_BUTTON_COLOR: Color = Color::srgb(0.15, 0.15, 0.18);
+pub const CHART_BUTTON_SELECTED_COLOR: Color = Color::srgb(0.3, 0.3, 0.4);
+pub const FAMILY_TREE_MAX_LINES: usize = 12; // Descendants listed in the family tree before it is cut short
+pub const EVENT_LOG_LENGTH: usize = 200; // Events kept for the log panel
+pub const EVENT_LOG_HEIGHT: f32 = 160.0;
+pub const EVENT_LOG_SCROLL_SPEED: f32 = 20.0; // Pixels per mouse wheel line
+pub const HISTORY_LENGTH: usize = (TICKS_PER_DAY * DAYS_PER_SEASON * 4 * 50) as usize; // Ticks of statistics kept for the charts (50 years)
 pub const HEADBAND_COLORS: [Color; 12] = [
         Color::srgb(1.0, 0.0, 0.0),     // Red
         Color::srgb(0.0, 1.0, 0.0),     // Green
@@ -1,17 +1,31 @@
 use bevy::prelude::*;
-use bevy::input::common_conditions::input_just_pressed;
+use bevy::input::common_conditions::{input_just_pressed, input_just_released};
 
 mod resources;
 mod systems;
 mod components;
 mod constants;
 
-use components::components::FoodTargetInvalidated;
+use components::components::{FoodTargetInvalidated, TileChanged, TileCostChanged, DisasterOccurred, SimEvent};
 
 use resources::{
-    game_state::GameState,
+    game_state::{GameState, SimulationSpeed, SingleStep},
     camera::{CameraZoom, CameraPosition},
-    ui_elements::{BandCenterVisualizationEnabled, LeftPanelState},
+    ui_elements::{BandCenterVisualizationEnabled, LeftPanelState, MapRenderMode, DragSelection, ControlGroups, ChartsVisible, ChartWindow, EventLogVisible},
+    band_center::{BandFlowField, BandFoodMemory, CreaturesInCamp, ManualBandMoves},
+    world_preset::WorldPreset,
+    game_grid::FootTraffic,
+    nav_graph::PathSearchBudget,
+    utility::UtilityConfig,
+    weather::Weather,
+    calendar::{PlantsFruiting, TimeOfDay},
+    technology::Technologies,
+    fog_of_war::FogOfWar,
+    history::History,
+    stats::{StatsConfig, StatsRecorder},
+    event_log::EventLog,
+    genealogy::{Genealogy, NextCreatureId},
+    replay::{Replay, ReplaySnapshots},
 };
 use systems::{
     ux::*,
@@ -20,10 +34,14 @@ use systems::{
     gameplay::*,
     creature::*,
     input::*,
+    weather::*,
+    replay::*,
 };
 use constants::*;
 
 fn main() {
+    // A replay brings the seed, preset and utility scorers of the run it was recorded from
+    let replay = Replay::from_args();
     App::new()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
@@ -37,66 +55,187 @@ fn main() {
             ..default()
         }))
         .init_state::<GameState>()
+        .init_resource::<SimulationSpeed>()
+        .init_resource::<SingleStep>()
         .init_resource::<CameraZoom>()
         .init_resource::<CameraPosition>()
         .init_resource::<BandCenterVisualizationEnabled>()
         .init_resource::<LeftPanelState>()
+        .init_resource::<DragSelection>()
+        .init_resource::<ControlGroups>()
+        .init_resource::<MapRenderMode>()
+        .init_resource::<ChartsVisible>()
+        .init_resource::<ChartWindow>()
+        .init_resource::<History>()
+        .insert_resource(StatsConfig::from_args())
+        .init_resource::<StatsRecorder>()
+        .insert_resource(EventLog::from_args())
+        .init_resource::<EventLogVisible>()
+        .init_resource::<Genealogy>()
+        .init_resource::<NextCreatureId>()
+        .insert_resource(replay.preset.unwrap_or_else(WorldPreset::from_args))
+        .insert_resource(replay.utility.clone().unwrap_or_else(UtilityConfig::from_args))
+        .insert_resource(replay)
+        .init_resource::<ReplaySnapshots>()
+        .init_resource::<Weather>()
+        .init_resource::<TimeOfDay>()
+        .init_resource::<PlantsFruiting>()
+        .init_resource::<Technologies>()
+        .init_resource::<BandFlowField>()
+        .init_resource::<BandFoodMemory>()
+        .init_resource::<CreaturesInCamp>()
+        .init_resource::<ManualBandMoves>()
+        .init_resource::<FogOfWar>()
+        .init_resource::<FootTraffic>()
+        .init_resource::<PathSearchBudget>()
         .add_event::<FoodTargetInvalidated>()
+        .add_event::<TileChanged>()
+        .add_event::<TileCostChanged>()
+        .add_event::<DisasterOccurred>()
+        .add_event::<SimEvent>()
         .add_systems(
             Startup, 
             (
                 setup_system,
                 setup_visualization_system,
                 spawn_ui,
+                start_replay_recording_system,
             ).chain(),
         )
         .add_systems(
             FixedUpdate, // System run every tick
             (
-                update_band_center_system,
-                check_manual_band_return_system,
+                spatial_grid_system,     // Rebuilt every tick too so ticks never see a stale grid
+                replay_playback_system,
+                (
+                    manual_band_move_system,
+                    update_band_center_system,
+                    check_manual_band_return_system,
+                    band_migration_system,
+                    band_flow_field_system,
+                    food_memory_sharing_system,
+                ).chain(),
                 // Intent-Driven Systems
-                goal_selection_system,      // Brain: assigns intents (WantsTo*)
-                idle_goal_selection_system,   // Convert WantsToIdle to actions
-                find_food_system,          // Convert WantsToEat to actions  
-                pathfinding_system,        // Convert ActionTravelTo to ActivePath
-                return_to_band_system,      // Convert WantsToReturnToBand to ActionTravelTo
-                perform_movement_system,    // Execute movement along ActivePath
-                perform_eat_system,        // Execute eating actions
-                food_target_notification_system, // Notify creatures when their targets become unavailable
-                handle_food_target_invalidated_system, // Handle food target invalidation events
-                procreation_system,        // Execute procreation actions
-                check_if_returned_to_band_system, // Remove OutsideBandRadius if returned to band
+                (
+                    goal_selection_system,      // Brain: assigns intents (WantsTo*)
+                    player_order_system,        // Player orders override the brain
+                    idle_goal_selection_system,   // Convert WantsToIdle to actions
+                    vision_system,             // Record food seen nearby in each creature's memory
+                    find_food_system,          // Convert WantsToEat to actions  
+                    find_water_system,         // Convert WantsToDrink to actions
+                    fall_asleep_system,        // Convert WantsToSleep to actions
+                    seek_warmth_system,        // Convert WantsToWarm to actions
+                    refill_path_search_budget_system, // Searches allowed this tick, shared by the next two
+                    path_validation_system,    // Replan routes whose target moved or terrain changed
+                    pathfinding_system,        // Convert ActionTravelTo to ActivePath
+                    return_to_band_system,      // Convert WantsToReturnToBand to ActionTravelTo
+                ).chain(),
+                // Action execution
+                (
+                    perform_movement_system,    // Execute movement along ActivePath
+                    perform_eat_system,        // Execute eating actions
+                    perform_drink_system,      // Execute drinking actions
+                    perform_sleep_system,      // Execute sleeping actions
+                    perform_warm_system,       // Execute warming actions
+                    food_target_notification_system, // Notify creatures when their targets become unavailable
+                    handle_food_target_invalidated_system, // Handle food target invalidation events
+                    procreation_system,        // Execute procreation actions
+                    check_if_returned_to_band_system, // Remove OutsideBandRadius if returned to band
+                    player_order_completion_system, // Clear player orders that are done
+                ).chain(),
                 // Core systems
-                pregnancy_system,
-                calorie_burn_system,
-                death_system,
-                //plant_propagation_system, // TODO: Remove when not needed
-                population_counter_system,
-                tick_counter_system,
-            ).chain().run_if(in_state(GameState::Running)),
+                (
+                    pregnancy_system,
+                    calorie_burn_system,
+                    thirst_system,
+                    fatigue_system,
+                    body_heat_system,
+                    swimming_system,
+                    build_raft_system,
+                    death_system,
+                    plant_propagation_system,
+                    population_counter_system,
+                    tick_counter_system,
+                    calendar_system,
+                    time_of_day_system,
+                    technology_discovery_system,
+                    seasonal_plant_system,
+                    trail_formation_system,
+                    soil_recovery_system,
+                    weather_system,
+                    log_disaster_system,
+                    nav_graph_invalidation_system,
+                ).chain(),
+                record_history_system,
+                stats_recorder_system,
+                genealogy_system,
+                event_log_system,
+                replay_keyframe_system,
+                finish_replay_seek_system,
+                finish_single_step_system,
+            ).chain().run_if(in_state(GameState::Running).or(single_step_requested)),
+        )
+        .add_systems(
+            FixedUpdate,
+            // Sprites go on at tick boundaries so archetype moves, and with them query
+            // order, are the same from run to run
+            (
+                (
+                    spawn_creature_visuals_system,
+                    spawn_plant_visuals_system,
+                ),
+                replay_snapshot_system, // Copies the world with its sprites already on
+            ).chain().after(finish_single_step_system),
         )
         .add_systems(
             Update, // System run every frame
             (
                 spatial_grid_system,
+                fog_of_war_system,
+                // Input
                 (
                     toggle_pause_system,
+                    simulation_speed_system,
+                    single_step_system,
                     band_center_toggle_system,
+                    map_render_mode_toggle_system,
+                    fog_of_war_toggle_system,
                     camera_zoom_system,
                     camera_pan_system,
-                    spawn_creature_visuals_system,
-                    spawn_plant_visuals_system,
+                    drag_selection_start_system.run_if(input_just_pressed(MouseButton::Left)),
+                    cursor_click_system.run_if(input_just_released(MouseButton::Left)),
+                    order_click_system.run_if(input_just_pressed(MouseButton::Right)),
+                    return_home_order_system,
+                    control_group_system,
+                    clear_selection_on_escape_system,
+                    charts_toggle_system,
+                    chart_window_button_system,
+                    event_log_toggle_system,
+                    event_log_scroll_system,
+                    replay_seek_system,
+                ),
+                replay_rewind_system, // Restarts the world when a seek goes back
+                // Visuals and UI
+                (
+                    update_tile_visuals_system,
+                    fog_of_war_plant_visibility_system,
+                    night_overlay_system,
                     update_creature_color_system,
                     update_creature_position_visuals_system,
                     update_population_text_system,
                     update_selected_panel_system,
+                    update_inspector_panel_system,
                     path_visualization_system,
                     cleanup_path_visualization_system,
                     band_center_visualization_system,
+                    selection_box_system,
                     update_tick_text_system,
-                    cursor_click_system.run_if(input_just_pressed(MouseButton::Left)),
-                    clear_selection_on_escape_system,
+                    update_date_text_system,
+                    update_speed_text_system,
+                    update_charts_system,
+                    update_event_log_panel_system,
+                    update_family_tree_system,
+                    update_replay_timeline_system,
                 ),
             ).chain(),
         )
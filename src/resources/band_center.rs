@@ -1,11 +1,37 @@
-use bevy::prelude::Resource;
-use crate::components::components::Position;
+use bevy::ecs::entity::{EntityMapper, MapEntities};
+use bevy::prelude::{Resource, Entity};
+use std::collections::HashSet;
+use crate::components::components::{Position, FoodMemory};
+use crate::resources::nav_graph::FlowField;
 
-#[derive(Resource)]
+#[derive(Resource, Clone)]
 pub struct BandCenter(pub Position);
 
-#[derive(Resource)]
+#[derive(Resource, Clone)]
 pub enum BandCenterMode {
     Auto,
-    Manual(Position),
-}
\ No newline at end of file
+    Manual(Position),    // Set by the player
+    Migrating(Position), // Moved by the band itself toward better foraging
+}
+
+// Band center moves made by hand (from, to), waiting to be reported as SimEvents on the next tick,
+// since clicks land between ticks and events sent while paused would never be read
+#[derive(Resource, Clone, Default)]
+pub struct ManualBandMoves(pub Vec<(Position, Position)>);
+// Shared route home for every creature heading to the band center, rebuilt as the center moves
+#[derive(Resource, Clone, Default)]
+pub struct BandFlowField(pub Option<FlowField>);
+
+// Food sightings pooled by everyone who has come back to camp
+#[derive(Resource, Clone, Default)]
+pub struct BandFoodMemory(pub FoodMemory);
+
+// Creatures that were within the band radius last tick, so those arriving can be told apart
+#[derive(Resource, Clone, Default)]
+pub struct CreaturesInCamp(pub HashSet<Entity>);
+
+impl MapEntities for CreaturesInCamp {
+    fn map_entities<M: EntityMapper>(&mut self, mapper: &mut M) {
+        self.0 = self.0.iter().map(|&entity| mapper.get_mapped(entity)).collect();
+    }
+}
@@ -0,0 +1,104 @@
+use bevy::prelude::Resource;
+use crate::constants::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    fn from_index(index: u32) -> Self {
+        match index % 4 {
+            0 => Self::Spring,
+            1 => Self::Summer,
+            2 => Self::Autumn,
+            _ => Self::Winter,
+        }
+    }
+
+    // Added to the latitude-based ambient temperature
+    pub fn temperature_offset(&self) -> f32 {
+        match self {
+            Self::Spring | Self::Autumn => 0.0,
+            Self::Summer => SUMMER_TEMPERATURE_OFFSET,
+            Self::Winter => WINTER_TEMPERATURE_OFFSET,
+        }
+    }
+
+    pub fn calorie_burn_multiplier(&self) -> f32 {
+        match self {
+            Self::Winter => WINTER_CALORIE_BURN_MULTIPLIER,
+            _ => 1.0,
+        }
+    }
+
+    // Plants only bear edible grain outside winter
+    pub fn plants_fruiting(&self) -> bool {
+        !matches!(self, Self::Winter)
+    }
+
+    // Plants only spread in spring
+    pub fn plants_growing(&self) -> bool {
+        matches!(self, Self::Spring)
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Calendar {
+    pub day: u32, // Day within the season, starting at 1
+    pub season: Season,
+    pub year: u32, // Starting at 1
+}
+
+impl Calendar {
+    pub fn from_tick(tick: u32) -> Self {
+        let total_days = tick / TICKS_PER_DAY;
+        let total_seasons = total_days / DAYS_PER_SEASON;
+        Self {
+            day: total_days % DAYS_PER_SEASON + 1,
+            season: Season::from_index(total_seasons),
+            year: total_seasons / 4 + 1,
+        }
+    }
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Self::from_tick(0)
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TimeOfDay {
+    pub hour: u32, // One tick per hour
+}
+
+impl TimeOfDay {
+    pub fn from_tick(tick: u32) -> Self {
+        Self { hour: tick % TICKS_PER_DAY }
+    }
+
+    // Firelight keeps the band active for a few extra hours each evening
+    pub fn is_night(&self, has_fire: bool) -> bool {
+        let night_start = if has_fire { NIGHT_START_HOUR + FIRE_EXTRA_HOURS } else { NIGHT_START_HOUR };
+        self.hour >= night_start || self.hour < NIGHT_END_HOUR
+    }
+
+    // 0 in full daylight, 1 in full night, with one hour of dusk and dawn
+    pub fn darkness(&self) -> f32 {
+        if self.hour >= NIGHT_START_HOUR || self.hour < NIGHT_END_HOUR {
+            1.0
+        } else if self.hour == NIGHT_START_HOUR - 1 || self.hour == NIGHT_END_HOUR {
+            0.5
+        } else {
+            0.0
+        }
+    }
+}
+
+// Whether plants currently bear grain; None until the first tick sets them for the season
+#[derive(Resource, Clone, Default)]
+pub struct PlantsFruiting(pub Option<bool>);
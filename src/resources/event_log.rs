@@ -0,0 +1,42 @@
+use bevy::prelude::Resource;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufWriter;
+use crate::components::components::SimEvent;
+use crate::constants::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct LoggedEvent {
+    pub tick: u32,
+    pub event: SimEvent,
+}
+
+// The most recent SimEvents for the log panel, and optionally every event written to
+// events_<seed>.jsonl (turned on with --event-log)
+#[derive(Resource, Default)]
+pub struct EventLog {
+    pub entries: VecDeque<LoggedEvent>, // Oldest first, at most EVENT_LOG_LENGTH
+    pub to_file: bool,
+    pub writer: Option<BufWriter<File>>,
+    pub failed: bool, // The file couldn't be written; only the in-game log is kept
+}
+
+impl EventLog {
+    pub fn from_args() -> Self {
+        Self {
+            to_file: std::env::args().any(|arg| arg == "--event-log"),
+            ..Self::default()
+        }
+    }
+
+    pub fn push(&mut self, entry: LoggedEvent) {
+        if self.entries.len() >= EVENT_LOG_LENGTH {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub fn file_name(seed: u32) -> String {
+        format!("events_{}.jsonl", seed)
+    }
+}
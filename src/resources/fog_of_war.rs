@@ -0,0 +1,70 @@
+use bevy::prelude::Resource;
+use std::collections::HashSet;
+use crate::components::components::Position;
+use crate::constants::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TileVisibility {
+    #[default]
+    Unexplored,
+    Explored, // Seen before, but no one is looking at it now
+    Visible,
+}
+
+// What the player can see: the union of the band's vision, and everything it has seen before
+#[derive(Resource, Clone)]
+pub struct FogOfWar {
+    pub enabled: bool, // When off, the whole map and every entity is shown for debugging
+    pub tiles: Vec<Vec<TileVisibility>>,
+    visible: HashSet<Position>,
+    pub changed: Vec<Position>, // Tiles whose visibility changed in the last update
+}
+
+impl Default for FogOfWar {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            tiles: vec![vec![TileVisibility::Unexplored; GRID_WIDTH]; GRID_HEIGHT],
+            visible: HashSet::new(),
+            changed: Vec::new(),
+        }
+    }
+}
+
+impl FogOfWar {
+    // How a tile should be shown, which is always fully visible with the fog turned off
+    pub fn visibility_at(&self, pos: Position) -> TileVisibility {
+        if self.enabled {
+            self.tiles[pos.y as usize][pos.x as usize]
+        } else {
+            TileVisibility::Visible
+        }
+    }
+
+    // Recomputes what is in view of the given viewers, each seeing VISION_RADIUS tiles around them
+    pub fn update(&mut self, viewers: impl Iterator<Item = Position>) {
+        let mut now = HashSet::new();
+        for viewer in viewers {
+            for dy in -VISION_RADIUS..=VISION_RADIUS {
+                for dx in -VISION_RADIUS..=VISION_RADIUS {
+                    let pos = Position { x: viewer.x + dx, y: viewer.y + dy };
+                    if dx * dx + dy * dy <= VISION_RADIUS * VISION_RADIUS
+                        && pos.x >= 0 && pos.x < GRID_WIDTH as i32 && pos.y >= 0 && pos.y < GRID_HEIGHT as i32 {
+                        now.insert(pos);
+                    }
+                }
+            }
+        }
+
+        self.changed.clear();
+        for &pos in self.visible.difference(&now) {
+            self.tiles[pos.y as usize][pos.x as usize] = TileVisibility::Explored;
+            self.changed.push(pos);
+        }
+        for &pos in now.difference(&self.visible) {
+            self.tiles[pos.y as usize][pos.x as usize] = TileVisibility::Visible;
+            self.changed.push(pos);
+        }
+        self.visible = now;
+    }
+}
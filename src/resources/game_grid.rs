@@ -1,8 +1,9 @@
 use bevy::prelude::{Resource, Entity};
 use std::collections::HashMap;
 use crate::components::components::Position;
+use crate::constants::*;
 
-#[derive(Resource)]
+#[derive(Resource, Clone)]
 pub struct GameGrid {
     pub tiles: Vec<Vec<Tile>>,
 }
@@ -11,13 +12,165 @@ pub struct GameGrid {
 pub enum TileKind {
     Empty,
     Dirt,
-    Water,
+    Water, // Salt water (ocean), too deep to cross without a boat
+    Shallows, // Salt water along the coast, shallow enough to swim
+    Desert,
+    Mountain,
+    Lake,       // Fresh water
+    River,      // Fresh water, shallow enough to ford
+    Floodplain, // Fertile land bordering a river
+}
+
+impl TileKind {
+    pub fn is_water(&self) -> bool {
+        matches!(self, TileKind::Water | TileKind::Shallows | TileKind::Lake | TileKind::River)
+    }
+
+    // Water a creature can drown in
+    pub fn is_deep_water(&self) -> bool {
+        matches!(self, TileKind::Water | TileKind::Lake)
+    }
+
+    pub fn is_fresh_water(&self) -> bool {
+        matches!(self, TileKind::Lake | TileKind::River)
+    }
+
+    // Fertility the soil settles at when left alone
+    pub fn natural_fertility(&self) -> f32 {
+        match self {
+            TileKind::Dirt => DIRT_FERTILITY,
+            TileKind::Floodplain => FLOODPLAIN_FERTILITY,
+            TileKind::Desert => DESERT_FERTILITY,
+            TileKind::Mountain => MOUNTAIN_FERTILITY,
+            _ => 0.0,
+        }
+    }
+}
+
+// How a creature crosses water, which decides what each tile costs to enter
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MovementMode {
+    Walk, // Swims shallows and lakes, can't cross open ocean
+    Boat,
+}
+
+// Ground worn down by foot traffic
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Surface {
+    #[default]
+    Natural,
+    Trail,
+    Road,
+}
+
+impl Surface {
+    // Added to a land tile's move_cost: rough ground off the beaten track is slowest
+    pub fn extra_cost(&self) -> u32 {
+        match self {
+            Self::Natural => OFF_TRAIL_COST,
+            Self::Trail => TRAIL_COST,
+            Self::Road => 0,
+        }
+    }
+
+    pub fn calorie_multiplier(&self) -> f32 {
+        match self {
+            Self::Natural => 1.0,
+            Self::Trail => TRAIL_CALORIE_MULTIPLIER,
+            Self::Road => ROAD_CALORIE_MULTIPLIER,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
 pub struct Tile {
     pub kind: TileKind,
     pub move_cost: i32,
+    pub elevation: f32, // Normalized height in [0, 1]; sea level is WATER_LEVEL
+    pub surface: Surface,
+    pub fertility: f32, // Soil fertility in [0, 1], drawn down by growth and harvests
+}
+
+impl Tile {
+    // Fertility rounded down to the steps the map shows
+    pub fn soil_level(&self) -> u8 {
+        (self.fertility * FERTILITY_LEVELS) as u8
+    }
+}
+
+impl GameGrid {
+    // Ambient temperature in °C: warmest at the equator (middle row), coldest at the map edges
+    pub fn temperature_at(&self, pos: Position) -> f32 {
+        let half_height = GRID_HEIGHT as f32 / 2.0;
+        let latitude = ((pos.y as f32 - half_height).abs() / half_height).min(1.0);
+        let mut temperature = EQUATOR_TEMPERATURE + (POLE_TEMPERATURE - EQUATOR_TEMPERATURE) * latitude;
+        let tile = &self.tiles[pos.y as usize][pos.x as usize];
+        // Air cools with altitude, and exposed mountain ridges are colder still
+        temperature -= (tile.elevation - WATER_LEVEL).max(0.0) * ELEVATION_TEMPERATURE_DROP;
+        if tile.kind == TileKind::Mountain {
+            temperature -= MOUNTAIN_TEMPERATURE_DROP;
+        }
+        temperature
+    }
+
+    // Cost of stepping onto a tile, or None if it is out of bounds or can't be entered
+    pub fn step_cost(&self, pos: Position, mode: MovementMode) -> Option<u32> {
+        if pos.x < 0 || pos.x >= GRID_WIDTH as i32 || pos.y < 0 || pos.y >= GRID_HEIGHT as i32 {
+            return None;
+        }
+        let tile = &self.tiles[pos.y as usize][pos.x as usize];
+        // Peaks are too steep to climb
+        if tile.elevation >= PEAK_ELEVATION {
+            return None;
+        }
+        let cost = match (tile.kind, mode) {
+            (TileKind::Empty, _) => 10,  // Standard cost for empty tiles
+            // Boats and rafts make any water quick to cross
+            (TileKind::Water | TileKind::Shallows | TileKind::Lake, MovementMode::Boat) => BOAT_MOVE_COST,
+            // Open ocean is too deep to swim across
+            (TileKind::Water, MovementMode::Walk) => return None,
+            // Lakes can be swum, slowly and at risk of drowning
+            (TileKind::Lake, MovementMode::Walk) => tile.move_cost as u32 * 10,
+            // Rivers are shallow enough to ford at their own move_cost
+            (TileKind::Shallows | TileKind::River, _) => tile.move_cost as u32,
+            // Land is quicker to cross where traffic has worn a trail or road
+            (TileKind::Dirt | TileKind::Desert | TileKind::Mountain | TileKind::Floodplain, _) => tile.move_cost as u32 + tile.surface.extra_cost(),
+        };
+        // Prevent unreasonably high costs
+        (cost <= 1000).then_some(cost)
+    }
+
+    // Cost of a single step between neighboring tiles: the cost of entering the destination,
+    // plus a penalty for climbing and a smaller one for picking a way down
+    pub fn move_cost(&self, from: Position, to: Position, mode: MovementMode) -> Option<u32> {
+        let cost = self.step_cost(to, mode)?;
+        if from.x < 0 || from.x >= GRID_WIDTH as i32 || from.y < 0 || from.y >= GRID_HEIGHT as i32 {
+            return Some(cost);
+        }
+        let slope = self.tiles[to.y as usize][to.x as usize].elevation - self.tiles[from.y as usize][from.x as usize].elevation;
+        let penalty = if slope > 0.0 { slope * UPHILL_COST_FACTOR } else { -slope * DOWNHILL_COST_FACTOR };
+        Some(cost + penalty.round() as u32)
+    }
+
+    // The closest tile to pos that can be entered, searching outward in rings
+    pub fn nearest_enterable(&self, pos: Position, mode: MovementMode) -> Option<Position> {
+        (0i32..=NEAREST_ENTERABLE_RADIUS).find_map(|radius| {
+            (-radius..=radius)
+                .flat_map(|dy| (-radius..=radius).map(move |dx| Position { x: pos.x + dx, y: pos.y + dy }))
+                .filter(|candidate| (candidate.x - pos.x).abs() == radius || (candidate.y - pos.y).abs() == radius)
+                .find(|candidate| self.step_cost(*candidate, mode).is_some())
+        })
+    }
+}
+
+// Footsteps per tile, decaying daily; enough of them wear trails and then roads
+#[derive(Resource, Clone)]
+pub struct FootTraffic(pub Vec<Vec<f32>>);
+
+impl Default for FootTraffic {
+    fn default() -> Self {
+        Self(vec![vec![0.0; GRID_WIDTH]; GRID_HEIGHT])
+    }
 }
 
 #[derive(Resource, Default)]
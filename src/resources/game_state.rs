@@ -1,8 +1,61 @@
-use bevy::prelude::States;
+use bevy::prelude::{States, Resource};
+use crate::constants::*;
 
 #[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum GameState {
     #[default]
     Running,
     Paused,
-}
\ No newline at end of file
+}
+
+// How fast ticks run, as a multiple of TICK_RATE_HZ
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SimulationSpeed {
+    Half,
+    #[default]
+    Normal,
+    Double,
+    Fast,
+    VeryFast,
+    Max, // As many ticks as the machine keeps up with
+}
+
+impl SimulationSpeed {
+    pub const ALL: [SimulationSpeed; 6] = [Self::Half, Self::Normal, Self::Double, Self::Fast, Self::VeryFast, Self::Max];
+
+    pub fn tick_rate_hz(&self) -> f64 {
+        match self {
+            Self::Half => TICK_RATE_HZ * 0.5,
+            Self::Normal => TICK_RATE_HZ,
+            Self::Double => TICK_RATE_HZ * 2.0,
+            Self::Fast => TICK_RATE_HZ * 5.0,
+            Self::VeryFast => TICK_RATE_HZ * 20.0,
+            Self::Max => MAX_TICK_RATE_HZ,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Half => "0.5x",
+            Self::Normal => "1x",
+            Self::Double => "2x",
+            Self::Fast => "5x",
+            Self::VeryFast => "20x",
+            Self::Max => "max",
+        }
+    }
+
+    pub fn faster(&self) -> Self {
+        let index = Self::ALL.iter().position(|speed| speed == self).unwrap_or(0);
+        Self::ALL[(index + 1).min(Self::ALL.len() - 1)]
+    }
+
+    pub fn slower(&self) -> Self {
+        let index = Self::ALL.iter().position(|speed| speed == self).unwrap_or(0);
+        Self::ALL[index.saturating_sub(1)]
+    }
+}
+
+// Set to run exactly one tick while paused
+#[derive(Resource, Default)]
+pub struct SingleStep(pub bool);
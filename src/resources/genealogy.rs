@@ -0,0 +1,91 @@
+use bevy::ecs::entity::{EntityMapper, MapEntities};
+use bevy::prelude::{Resource, Entity};
+use std::collections::HashMap;
+use crate::components::components::{DeathCause, CreatureId};
+
+// Everything known about one creature's place in the family tree, alive or dead
+#[derive(Clone, Debug)]
+pub struct GenealogyRecord {
+    pub id: CreatureId,
+    pub mother: Option<Entity>, // None for the founders the world started with
+    pub founder: Entity,
+    pub generation: u32,        // Founders are generation 0
+    pub children: Vec<Entity>,
+    pub born_tick: u32,
+    pub death: Option<(u32, DeathCause)>,
+}
+
+// The CreatureId the next creature born is given
+#[derive(Resource, Clone, Default)]
+pub struct NextCreatureId(pub u32);
+
+impl NextCreatureId {
+    pub fn take(&mut self) -> CreatureId {
+        self.0 += 1;
+        CreatureId(self.0)
+    }
+}
+
+// Family records of every creature that has lived, kept after they die so lineages can be traced
+#[derive(Resource, Clone, Default)]
+pub struct Genealogy {
+    pub records: HashMap<Entity, GenealogyRecord>,
+}
+
+impl Genealogy {
+    // Mother, grandmother and so on back to the founder
+    pub fn ancestors(&self, entity: Entity) -> Vec<Entity> {
+        let mut ancestors = Vec::new();
+        let mut current = self.records.get(&entity).and_then(|record| record.mother);
+        while let Some(mother) = current {
+            ancestors.push(mother);
+            current = self.records.get(&mother).and_then(|record| record.mother);
+        }
+        ancestors
+    }
+
+    // Every descendant in depth-first order, with how many generations below entity they are
+    pub fn descendants(&self, entity: Entity) -> Vec<(Entity, u32)> {
+        let mut descendants = Vec::new();
+        let mut stack: Vec<(Entity, u32)> = self.children_of(entity).iter().rev().map(|&child| (child, 1)).collect();
+        while let Some((current, depth)) = stack.pop() {
+            descendants.push((current, depth));
+            stack.extend(self.children_of(current).iter().rev().map(|&child| (child, depth + 1)));
+        }
+        descendants
+    }
+
+    pub fn id_of(&self, entity: Entity) -> Option<CreatureId> {
+        self.records.get(&entity).map(|record| record.id)
+    }
+
+    // How the UI and logs name a creature: by its CreatureId, which matches across replays of a run
+    pub fn name(&self, entity: Entity) -> String {
+        match self.id_of(entity) {
+            Some(id) => format!("#{}", id.0),
+            None => entity.to_string(),
+        }
+    }
+
+    pub fn children_of(&self, entity: Entity) -> &[Entity] {
+        self.records.get(&entity).map_or(&[], |record| &record.children)
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.records.get(&entity).is_some_and(|record| record.death.is_none())
+    }
+}
+
+// Records stay keyed by the creature they describe when a replay snapshot is copied or restored
+impl MapEntities for Genealogy {
+    fn map_entities<M: EntityMapper>(&mut self, mapper: &mut M) {
+        self.records = std::mem::take(&mut self.records).into_iter()
+            .map(|(entity, mut record)| {
+                record.mother.map_entities(mapper);
+                record.founder.map_entities(mapper);
+                record.children.map_entities(mapper);
+                (mapper.get_mapped(entity), record)
+            })
+            .collect();
+    }
+}
@@ -0,0 +1,36 @@
+use bevy::prelude::Resource;
+use std::collections::VecDeque;
+use crate::components::components::DeathCause;
+use crate::constants::*;
+
+// Statistics for a single tick
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HistorySample {
+    pub population: u32,
+    pub births: u32,
+    pub deaths: [u32; DeathCause::ALL.len()], // Indexed like DeathCause::ALL
+    pub plants: u32,
+    pub average_calories: f32,
+    pub pregnant: u32,
+}
+
+// Per-tick statistics for the charts; the oldest are dropped after HISTORY_LENGTH ticks
+#[derive(Resource, Clone, Default)]
+pub struct History {
+    pub samples: VecDeque<HistorySample>,
+}
+
+impl History {
+    pub fn push(&mut self, sample: HistorySample) {
+        if self.samples.len() >= HISTORY_LENGTH {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    // The most recent samples, at most ticks of them
+    pub fn latest(&self, ticks: Option<usize>) -> impl Iterator<Item = &HistorySample> {
+        let count = ticks.map_or(self.samples.len(), |ticks| ticks.min(self.samples.len()));
+        self.samples.range(self.samples.len() - count..)
+    }
+}
@@ -3,4 +3,16 @@ pub mod game_grid;
 pub mod ui_elements;
 pub mod band_center;
 pub mod seed;
-pub mod camera;
\ No newline at end of file
+pub mod camera;
+pub mod world_preset;
+pub mod utility;
+pub mod calendar;
+pub mod weather;
+pub mod technology;
+pub mod nav_graph;
+pub mod fog_of_war;
+pub mod history;
+pub mod stats;
+pub mod event_log;
+pub mod genealogy;
+pub mod replay;
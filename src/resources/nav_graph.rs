@@ -0,0 +1,548 @@
+use bevy::prelude::Resource;
+use pathfinding::prelude::{astar, dijkstra_all};
+use std::collections::HashMap;
+use crate::components::components::Position;
+use crate::constants::*;
+use crate::resources::game_grid::{GameGrid, MovementMode};
+
+// 8-directional movement: E, W, S, N and diagonals
+const NEIGHBOR_OFFSETS: [(i32, i32); 8] = [
+    (1, 0), (-1, 0), (0, 1), (0, -1),
+    (1, 1), (-1, 1), (1, -1), (-1, -1),
+];
+
+// Rectangle a search may not leave, max bounds exclusive
+#[derive(Clone, Copy, Debug)]
+pub struct Bounds {
+    pub min_x: i32,
+    pub min_y: i32,
+    pub max_x: i32,
+    pub max_y: i32,
+}
+
+impl Bounds {
+    pub fn full() -> Self {
+        Self { min_x: 0, min_y: 0, max_x: GRID_WIDTH as i32, max_y: GRID_HEIGHT as i32 }
+    }
+
+    // Square of the given radius around a tile, cut to the grid
+    pub fn around(center: Position, radius: i32) -> Self {
+        Self {
+            min_x: (center.x - radius).max(0),
+            min_y: (center.y - radius).max(0),
+            max_x: (center.x + radius + 1).min(GRID_WIDTH as i32),
+            max_y: (center.y + radius + 1).min(GRID_HEIGHT as i32),
+        }
+    }
+
+    fn contains(&self, pos: Position) -> bool {
+        pos.x >= self.min_x && pos.x < self.max_x && pos.y >= self.min_y && pos.y < self.max_y
+    }
+}
+
+fn successors(grid: &GameGrid, pos: Position, bounds: Bounds, mode: MovementMode) -> impl Iterator<Item = (Position, u32)> + '_ {
+    NEIGHBOR_OFFSETS.iter().filter_map(move |(dx, dy)| {
+        let next = Position { x: pos.x + dx, y: pos.y + dy };
+        if !bounds.contains(next) {
+            return None;
+        }
+        grid.move_cost(pos, next, mode).map(|cost| (next, cost))
+    })
+}
+
+// Chebyshev distance heuristic for 8-directional grids (admissible when step costs >= 1)
+pub fn chebyshev(a: Position, b: Position) -> u32 {
+    i32::max((a.x - b.x).abs(), (a.y - b.y).abs()) as u32
+}
+
+// Plain A* confined to a rectangle of the grid
+pub fn astar_within(grid: &GameGrid, start: Position, end: Position, bounds: Bounds, mode: MovementMode) -> Option<(Vec<Position>, u32)> {
+    astar(
+        &start,
+        |p| successors(grid, *p, bounds, mode),
+        |p| chebyshev(*p, end),
+        |p| *p == end,
+    )
+}
+
+// A* that gives up once it has expanded max_nodes tiles, for searches that could otherwise flood a landmass
+pub fn astar_capped(grid: &GameGrid, start: Position, end: Position, bounds: Bounds, mode: MovementMode, max_nodes: usize) -> Option<(Vec<Position>, u32)> {
+    let mut expanded = 0;
+    astar(
+        &start,
+        |p| {
+            expanded += 1;
+            let within_cap = expanded <= max_nodes;
+            successors(grid, *p, bounds, mode).filter(move |_| within_cap)
+        },
+        |p| chebyshev(*p, end),
+        |p| *p == end,
+    )
+}
+
+// Hierarchical searches still allowed this tick, shared by everything that plans or repairs routes
+#[derive(Resource, Clone)]
+pub struct PathSearchBudget(pub usize);
+
+impl Default for PathSearchBudget {
+    fn default() -> Self {
+        Self(MAX_PATH_REQUESTS_PER_TICK)
+    }
+}
+
+impl PathSearchBudget {
+    pub fn take(&mut self) -> bool {
+        let available = self.0 > 0;
+        if available {
+            self.0 -= 1;
+        }
+        available
+    }
+}
+
+// Hierarchical (HPA*) search graph: the grid is cut into clusters, linked by transition
+// tiles on their shared borders. Long searches run over transitions, then get refined
+// into tile paths one cluster at a time. Walkers and boats see different terrain, so
+// each movement mode keeps its own layer.
+#[derive(Resource, Clone)]
+pub struct NavGraph {
+    walk: NavLayer,
+    boat: NavLayer,
+}
+
+impl NavGraph {
+    pub fn new(grid: &GameGrid) -> Self {
+        Self {
+            walk: NavLayer::new(grid, MovementMode::Walk),
+            boat: NavLayer::new(grid, MovementMode::Boat),
+        }
+    }
+
+    pub fn find_path(&mut self, grid: &GameGrid, start: Position, goal: Position, mode: MovementMode) -> Option<Vec<Position>> {
+        match mode {
+            MovementMode::Walk => self.walk.find_path(grid, start, goal),
+            MovementMode::Boat => self.boat.find_path(grid, start, goal),
+        }
+    }
+
+    pub fn invalidate(&mut self, grid: &GameGrid, pos: Position) {
+        self.walk.invalidate(grid, pos);
+        self.boat.invalidate(grid, pos);
+    }
+
+    // A cost change leaves the transitions in place; only the cached costs across the tile's cluster go
+    pub fn invalidate_costs(&mut self, pos: Position) {
+        self.walk.invalidate_costs(pos);
+        self.boat.invalidate_costs(pos);
+    }
+
+    // Whether any route joins two tiles, at whatever cost, answered without a search
+    pub fn reachable(&mut self, grid: &GameGrid, start: Position, goal: Position, mode: MovementMode) -> bool {
+        match mode {
+            MovementMode::Walk => self.walk.connected(grid, start, goal),
+            MovementMode::Boat => self.boat.connected(grid, start, goal),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct NavLayer {
+    mode: MovementMode,
+    clusters_x: i32,
+    clusters_y: i32,
+    // Transition pairs between a cluster and its right or lower neighbor, keyed by (cluster, neighbor)
+    borders: HashMap<(usize, usize), Vec<(Position, Position)>>,
+    // Costs between a cluster's transitions, filled in the first time a search crosses it
+    intra_edges: HashMap<usize, HashMap<Position, Vec<(Position, u32)>>>,
+    // Connected region of each tile, row by row, 0 where it can't be entered. Tiles share a region
+    // when a route joins them, so regions answer reachability that the sparse transitions can't
+    regions: Vec<u32>,
+    regions_stale: bool, // Terrain changed since the regions were labelled
+}
+
+impl NavLayer {
+    fn new(grid: &GameGrid, mode: MovementMode) -> Self {
+        let mut layer = Self {
+            mode,
+            clusters_x: GRID_WIDTH.div_ceil(NAV_CLUSTER_SIZE as usize) as i32,
+            clusters_y: GRID_HEIGHT.div_ceil(NAV_CLUSTER_SIZE as usize) as i32,
+            borders: HashMap::new(),
+            intra_edges: HashMap::new(),
+            regions: Vec::new(),
+            regions_stale: false,
+        };
+        for cy in 0..layer.clusters_y {
+            for cx in 0..layer.clusters_x {
+                layer.build_border(grid, cx, cy, true);
+                layer.build_border(grid, cx, cy, false);
+            }
+        }
+        layer.label_regions(grid);
+        layer
+    }
+
+    fn find_path(&mut self, grid: &GameGrid, start: Position, goal: Position) -> Option<Vec<Position>> {
+        // Goals across open water or behind a ridge of peaks can't be reached at all
+        if !self.connected(grid, start, goal) {
+            return None;
+        }
+        // Short trips are cheap enough to search directly, kept to the clusters around both ends;
+        // one whose route leaves them goes over the abstract graph instead
+        if chebyshev(start, goal) <= NAV_CLUSTER_SIZE as u32
+            && let Some((path, _)) = astar_within(grid, start, goal, trip_bounds(start, goal), self.mode)
+        {
+            return Some(path);
+        }
+        self.find_abstract_path(grid, start, goal)
+            .and_then(|waypoints| self.refine(grid, &waypoints))
+            // The abstract graph has only a transition or two per border and can miss a route the
+            // regions say exists; the direct search is capped so one winding far around can't stall the tick
+            .or_else(|| astar_capped(grid, start, goal, Bounds::full(), self.mode, NAV_FALLBACK_MAX_NODES).map(|(path, _)| path))
+    }
+
+    // Rebuilds the entrances around a changed tile and drops the cached costs that depended on it
+    fn invalidate(&mut self, grid: &GameGrid, pos: Position) {
+        self.regions_stale = true;
+        let (cx, cy) = cluster_of(pos);
+        self.build_border(grid, cx, cy, true);
+        self.build_border(grid, cx, cy, false);
+        if cx > 0 {
+            self.build_border(grid, cx - 1, cy, true);
+        }
+        if cy > 0 {
+            self.build_border(grid, cx, cy - 1, false);
+        }
+        for (dx, dy) in [(0, 0), (1, 0), (-1, 0), (0, 1), (0, -1)] {
+            if let Some(index) = self.cluster_index(cx + dx, cy + dy) {
+                self.intra_edges.remove(&index);
+            }
+        }
+    }
+
+    fn invalidate_costs(&mut self, pos: Position) {
+        let (cx, cy) = cluster_of(pos);
+        if let Some(index) = self.cluster_index(cx, cy) {
+            self.intra_edges.remove(&index);
+        }
+    }
+
+    // A start that can't be entered, like a swimmer's tile after a flood, joins the regions of its neighbors
+    fn connected(&mut self, grid: &GameGrid, start: Position, goal: Position) -> bool {
+        if self.regions_stale {
+            self.label_regions(grid);
+        }
+        let Some(goal_region) = self.region_at(goal) else {
+            return false;
+        };
+        self.region_at(start) == Some(goal_region)
+            || NEIGHBOR_OFFSETS.iter().any(|(dx, dy)| self.region_at(Position { x: start.x + dx, y: start.y + dy }) == Some(goal_region))
+    }
+
+    // --- Helper Functions ---
+
+    fn region_at(&self, pos: Position) -> Option<u32> {
+        if !Bounds::full().contains(pos) {
+            return None;
+        }
+        let region = self.regions[pos.y as usize * GRID_WIDTH + pos.x as usize];
+        (region != 0).then_some(region)
+    }
+
+    // Flood fills the enterable tiles; whole-grid, but only rerun after a TileChanged
+    fn label_regions(&mut self, grid: &GameGrid) {
+        let mut regions = vec![0; GRID_WIDTH * GRID_HEIGHT];
+        let mut next_region = 0;
+        let mut stack = Vec::new();
+        for y in 0..GRID_HEIGHT as i32 {
+            for x in 0..GRID_WIDTH as i32 {
+                let pos = Position { x, y };
+                if regions[y as usize * GRID_WIDTH + x as usize] != 0 || grid.step_cost(pos, self.mode).is_none() {
+                    continue;
+                }
+                next_region += 1;
+                regions[y as usize * GRID_WIDTH + x as usize] = next_region;
+                stack.push(pos);
+                while let Some(current) = stack.pop() {
+                    for (dx, dy) in NEIGHBOR_OFFSETS {
+                        let neighbor = Position { x: current.x + dx, y: current.y + dy };
+                        if !Bounds::full().contains(neighbor) {
+                            continue;
+                        }
+                        let index = neighbor.y as usize * GRID_WIDTH + neighbor.x as usize;
+                        if regions[index] == 0 && grid.step_cost(neighbor, self.mode).is_some() {
+                            regions[index] = next_region;
+                            stack.push(neighbor);
+                        }
+                    }
+                }
+            }
+        }
+        self.regions = regions;
+        self.regions_stale = false;
+    }
+
+    fn cluster_index(&self, cx: i32, cy: i32) -> Option<usize> {
+        (cx >= 0 && cx < self.clusters_x && cy >= 0 && cy < self.clusters_y)
+            .then(|| (cy * self.clusters_x + cx) as usize)
+    }
+
+    // Keys of the up to four borders a cluster shares, and whether the cluster is the first of the pair
+    fn border_keys(&self, cx: i32, cy: i32) -> Vec<((usize, usize), bool)> {
+        let Some(index) = self.cluster_index(cx, cy) else {
+            return Vec::new();
+        };
+        [
+            (self.cluster_index(cx + 1, cy), true),
+            (self.cluster_index(cx, cy + 1), true),
+            (self.cluster_index(cx - 1, cy), false),
+            (self.cluster_index(cx, cy - 1), false),
+        ]
+        .into_iter()
+        .filter_map(|(neighbor, own_is_first)| {
+            neighbor.map(|neighbor| if own_is_first { ((index, neighbor), true) } else { ((neighbor, index), false) })
+        })
+        .collect()
+    }
+
+    // Places transitions along the border between a cluster and its right (horizontal) or lower neighbor
+    fn build_border(&mut self, grid: &GameGrid, cx: i32, cy: i32, horizontal: bool) {
+        let (nx, ny) = if horizontal { (cx + 1, cy) } else { (cx, cy + 1) };
+        let (Some(index), Some(neighbor)) = (self.cluster_index(cx, cy), self.cluster_index(nx, ny)) else {
+            return;
+        };
+        let bounds = cluster_bounds(cx, cy);
+        let pairs: Vec<(Position, Position)> = if horizontal {
+            (bounds.min_y..bounds.max_y)
+                .map(|y| (Position { x: bounds.max_x - 1, y }, Position { x: bounds.max_x, y }))
+                .collect()
+        } else {
+            (bounds.min_x..bounds.max_x)
+                .map(|x| (Position { x, y: bounds.max_y - 1 }, Position { x, y: bounds.max_y }))
+                .collect()
+        };
+
+        // Entrances are runs of crossable tile pairs, split where land meets open water
+        let mut transitions = Vec::new();
+        let mut run = Vec::new();
+        let mut run_class = None;
+        for (a, b) in pairs {
+            let class = match (grid.step_cost(a, self.mode), grid.step_cost(b, self.mode)) {
+                (Some(cost_a), Some(cost_b)) => Some(cost_a.max(cost_b) >= NAV_WATER_COST),
+                _ => None,
+            };
+            if class != run_class {
+                push_entrance(&mut transitions, &run);
+                run.clear();
+                run_class = class;
+            }
+            if class.is_some() {
+                run.push((a, b));
+            }
+        }
+        push_entrance(&mut transitions, &run);
+        self.borders.insert((index, neighbor), transitions);
+    }
+
+    fn transitions_of(&self, cx: i32, cy: i32) -> Vec<Position> {
+        let mut transitions = Vec::new();
+        for (key, own_is_first) in self.border_keys(cx, cy) {
+            for &(a, b) in self.borders.get(&key).into_iter().flatten() {
+                let own = if own_is_first { a } else { b };
+                if !transitions.contains(&own) {
+                    transitions.push(own);
+                }
+            }
+        }
+        transitions
+    }
+
+    // Transitions on the far side of a border that a transition connects to
+    fn links_of(&self, pos: Position) -> Vec<Position> {
+        let (cx, cy) = cluster_of(pos);
+        let mut links = Vec::new();
+        for (key, _) in self.border_keys(cx, cy) {
+            for &(a, b) in self.borders.get(&key).into_iter().flatten() {
+                if a == pos {
+                    links.push(b);
+                } else if b == pos {
+                    links.push(a);
+                }
+            }
+        }
+        links
+    }
+
+    fn ensure_intra_edges(&mut self, grid: &GameGrid, cx: i32, cy: i32) {
+        let Some(index) = self.cluster_index(cx, cy) else {
+            return;
+        };
+        if self.intra_edges.contains_key(&index) {
+            return;
+        }
+        let bounds = cluster_bounds(cx, cy);
+        let transitions = self.transitions_of(cx, cy);
+        let mut edges = HashMap::new();
+        for &from in &transitions {
+            let reached = dijkstra_all(&from, |p| successors(grid, *p, bounds, self.mode));
+            let costs = transitions.iter()
+                .filter_map(|to| reached.get(to).map(|(_, cost)| (*to, *cost)))
+                .collect();
+            edges.insert(from, costs);
+        }
+        self.intra_edges.insert(index, edges);
+    }
+
+    // Local costs from a tile to the transitions of its own cluster
+    fn costs_to_transitions(&self, grid: &GameGrid, pos: Position) -> HashMap<Position, u32> {
+        let (cx, cy) = cluster_of(pos);
+        let reached = dijkstra_all(&pos, |p| successors(grid, *p, cluster_bounds(cx, cy), self.mode));
+        self.transitions_of(cx, cy).into_iter()
+            .filter_map(|t| reached.get(&t).map(|(_, cost)| (t, *cost)))
+            .collect()
+    }
+
+    // A* over transitions; returns the start, the transitions crossed and the goal
+    fn find_abstract_path(&mut self, grid: &GameGrid, start: Position, goal: Position) -> Option<Vec<Position>> {
+        let mut start_edges: Vec<(Position, u32)> = self.costs_to_transitions(grid, start).into_iter().collect();
+        // HashMap order varies between runs; a fixed order keeps equal-cost paths the same for replays
+        start_edges.sort_by_key(|(pos, _)| (pos.x, pos.y));
+        // Costs measured outward from the goal; close enough to guide the search, refinement finds the real path
+        let goal_edges = self.costs_to_transitions(grid, goal);
+        let goal_cluster = cluster_of(goal);
+
+        astar(
+            &start,
+            |p| {
+                let (cx, cy) = cluster_of(*p);
+                let mut next = if *p == start {
+                    start_edges.clone()
+                } else {
+                    self.ensure_intra_edges(grid, cx, cy);
+                    self.cluster_index(cx, cy)
+                        .and_then(|index| self.intra_edges[&index].get(p).cloned())
+                        .unwrap_or_default()
+                };
+                next.extend(self.links_of(*p).into_iter().filter_map(|n| grid.move_cost(*p, n, self.mode).map(|cost| (n, cost))));
+                if (cx, cy) == goal_cluster && let Some(&cost) = goal_edges.get(p) {
+                    next.push((goal, cost));
+                }
+                next
+            },
+            |p| chebyshev(*p, goal),
+            |p| *p == goal,
+        ).map(|(waypoints, _)| waypoints)
+    }
+
+    // Expands consecutive waypoints into tiles: border crossings are single steps,
+    // everything else is a local search inside one cluster
+    fn refine(&self, grid: &GameGrid, waypoints: &[Position]) -> Option<Vec<Position>> {
+        let mut path = vec![*waypoints.first()?];
+        for pair in waypoints.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            let (cx, cy) = cluster_of(from);
+            if cluster_of(to) != (cx, cy) {
+                path.push(to);
+                continue;
+            }
+            let (segment, _) = astar_within(grid, from, to, cluster_bounds(cx, cy), self.mode)?;
+            path.extend(segment.into_iter().skip(1));
+        }
+        Some(path)
+    }
+}
+
+// Dijkstra map toward one target: every tile within a radius knows its next step,
+// so any number of creatures heading there can share it without searching.
+// Built for walkers; its routes are just as valid for creatures with boats.
+#[derive(Clone)]
+pub struct FlowField {
+    pub target: Position,
+    bounds: Bounds,
+    next_steps: HashMap<Position, Position>,
+}
+
+impl FlowField {
+    pub fn new(grid: &GameGrid, target: Position, radius: i32) -> Self {
+        let bounds = Bounds::around(target, radius);
+        // Searching outward from the target, so each step is costed in the direction it will be walked
+        let next_steps = dijkstra_all(&target, |&p| {
+            NEIGHBOR_OFFSETS.iter().filter_map(move |(dx, dy)| {
+                let from = Position { x: p.x + dx, y: p.y + dy };
+                if !bounds.contains(from) || grid.step_cost(from, MovementMode::Walk).is_none() {
+                    return None;
+                }
+                grid.move_cost(from, p, MovementMode::Walk).map(|cost| (from, cost))
+            })
+        })
+        .into_iter()
+        .map(|(pos, (next, _))| (pos, next))
+        .collect();
+        Self { target, bounds, next_steps }
+    }
+
+    // Whether the field is close enough to a destination to stand in for a search to it
+    pub fn serves(&self, destination: Position) -> bool {
+        chebyshev(self.target, destination) <= FLOW_FIELD_REBUILD_DISTANCE as u32
+    }
+
+    pub fn covers(&self, pos: Position) -> bool {
+        self.bounds.contains(pos)
+    }
+
+    // Follows the field from a tile until it is near a destination the field serves, then walks the
+    // last few tiles with a short local search, since the destination needn't be the target itself.
+    // None outside the field or where either part is unreachable
+    pub fn path_to(&self, grid: &GameGrid, start: Position, destination: Position) -> Option<Vec<Position>> {
+        let mut path = vec![start];
+        let mut current = start;
+        while chebyshev(current, destination) > FLOW_FIELD_REBUILD_DISTANCE as u32 {
+            current = *self.next_steps.get(&current)?;
+            path.push(current);
+        }
+        if current != destination {
+            let bounds = Bounds::around(destination, 2 * FLOW_FIELD_REBUILD_DISTANCE);
+            let (last_leg, _) = astar_within(grid, current, destination, bounds, MovementMode::Walk)?;
+            path.extend(last_leg.into_iter().skip(1));
+        }
+        Some(path)
+    }
+}
+
+fn cluster_of(pos: Position) -> (i32, i32) {
+    (pos.x / NAV_CLUSTER_SIZE, pos.y / NAV_CLUSTER_SIZE)
+}
+
+fn cluster_bounds(cx: i32, cy: i32) -> Bounds {
+    Bounds {
+        min_x: cx * NAV_CLUSTER_SIZE,
+        min_y: cy * NAV_CLUSTER_SIZE,
+        max_x: ((cx + 1) * NAV_CLUSTER_SIZE).min(GRID_WIDTH as i32),
+        max_y: ((cy + 1) * NAV_CLUSTER_SIZE).min(GRID_HEIGHT as i32),
+    }
+}
+
+// The clusters holding both ends of a trip, widened by NAV_SEARCH_MARGIN
+fn trip_bounds(start: Position, goal: Position) -> Bounds {
+    let (start_x, start_y) = cluster_of(start);
+    let (goal_x, goal_y) = cluster_of(goal);
+    let (a, b) = (cluster_bounds(start_x, start_y), cluster_bounds(goal_x, goal_y));
+    Bounds {
+        min_x: (a.min_x.min(b.min_x) - NAV_SEARCH_MARGIN).max(0),
+        min_y: (a.min_y.min(b.min_y) - NAV_SEARCH_MARGIN).max(0),
+        max_x: (a.max_x.max(b.max_x) + NAV_SEARCH_MARGIN).min(GRID_WIDTH as i32),
+        max_y: (a.max_y.max(b.max_y) + NAV_SEARCH_MARGIN).min(GRID_HEIGHT as i32),
+    }
+}
+
+// Wide entrances get a transition at each end, narrow ones a single one in the middle
+fn push_entrance(transitions: &mut Vec<(Position, Position)>, run: &[(Position, Position)]) {
+    if run.is_empty() {
+        return;
+    }
+    if run.len() >= NAV_ENTRANCE_SPLIT_LENGTH {
+        transitions.push(run[0]);
+        transitions.push(run[run.len() - 1]);
+    } else {
+        transitions.push(run[run.len() / 2]);
+    }
+}
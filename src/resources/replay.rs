@@ -0,0 +1,319 @@
+use bevy::ecs::component::{ComponentId, ComponentTicks, Tick};
+use bevy::ecs::event::EventCursor;
+use bevy::prelude::{Entity, Resource, warn};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use crate::components::components::{Position, TileChanged};
+use crate::constants::*;
+use crate::resources::{
+    band_center::{BandCenter, BandCenterMode, BandFlowField, BandFoodMemory, CreaturesInCamp, ManualBandMoves},
+    calendar::{Calendar, PlantsFruiting, TimeOfDay},
+    event_log::LoggedEvent,
+    fog_of_war::FogOfWar,
+    game_grid::{FootTraffic, GameGrid},
+    genealogy::{Genealogy, NextCreatureId},
+    history::History,
+    nav_graph::{NavGraph, PathSearchBudget},
+    seed::SimRng,
+    technology::Technologies,
+    ui_elements::{PopulationCount, TickCount},
+    utility::UtilityConfig,
+    weather::Weather,
+    world_preset::WorldPreset,
+};
+
+// A player order as a replay stores it; the plant to eat is found again by its position
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReplayOrder {
+    MoveTo(Position),
+    Eat(Position),
+    BuildRaft(Position),
+    ReturnHome,
+}
+
+// A player input that affects the simulation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReplayInput {
+    Pause,
+    Resume,
+    BandCenter(Position),
+    Order { creature: u32, order: ReplayOrder }, // creature is its CreatureId
+}
+
+impl ReplayInput {
+    fn to_words(self) -> String {
+        match self {
+            Self::Pause => "pause".to_string(),
+            Self::Resume => "resume".to_string(),
+            Self::BandCenter(pos) => format!("band-center {} {}", pos.x, pos.y),
+            Self::Order { creature, order } => match order {
+                ReplayOrder::MoveTo(pos) => format!("order {} move {} {}", creature, pos.x, pos.y),
+                ReplayOrder::Eat(pos) => format!("order {} eat {} {}", creature, pos.x, pos.y),
+                ReplayOrder::BuildRaft(pos) => format!("order {} raft {} {}", creature, pos.x, pos.y),
+                ReplayOrder::ReturnHome => format!("order {} home", creature),
+            },
+        }
+    }
+
+    fn from_words(words: &[&str]) -> Option<Self> {
+        let position = |x: &str, y: &str| Some(Position { x: x.parse().ok()?, y: y.parse().ok()? });
+        match words {
+            ["pause"] => Some(Self::Pause),
+            ["resume"] => Some(Self::Resume),
+            ["band-center", x, y] => Some(Self::BandCenter(position(x, y)?)),
+            ["order", creature, rest @ ..] => {
+                let order = match rest {
+                    ["move", x, y] => ReplayOrder::MoveTo(position(x, y)?),
+                    ["eat", x, y] => ReplayOrder::Eat(position(x, y)?),
+                    ["raft", x, y] => ReplayOrder::BuildRaft(position(x, y)?),
+                    ["home"] => ReplayOrder::ReturnHome,
+                    _ => return None,
+                };
+                Some(Self::Order { creature: creature.parse().ok()?, order })
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ReplayMode {
+    #[default]
+    Off,
+    Recording,
+    Playing,
+}
+
+// Recording or playback of a run. A replay is a text file holding the world seed and preset and the
+// utility scorers the creatures decided by ("utility <scorer>"), then one line per player input ("input <tick> ...") and a checksum of the world every
+// REPLAY_KEYFRAME_INTERVAL ticks ("keyframe <tick> <checksum>") so playback can tell when it drifts.
+// Inputs made after tick N are applied before tick N + 1, as they were when recorded.
+// --record-replay records to replay_<seed>.txt; --replay <file> plays one back, and
+// --seek <tick> then fast-forwards playback to that tick
+#[derive(Resource, Default)]
+pub struct Replay {
+    pub mode: ReplayMode,
+    pub seed: Option<u32>,
+    pub preset: Option<WorldPreset>,
+    pub utility: Option<UtilityConfig>,
+    pub inputs: VecDeque<(u32, ReplayInput)>, // Inputs still to be played back, in tick order
+    pub keyframes: VecDeque<(u32, u64)>,      // Checksums still to be compared, in tick order
+    pub desynced: bool,
+    pub seek_target: Option<u32>,  // Tick playback is fast-forwarding to
+    pub pending_seek: Option<u32>, // Tick to seek to, from --seek, not yet started
+    pub rewind_requested: bool,    // The world is to restart from its first tick before seeking
+    pub end_tick: u32,             // Last tick the recording has an input or keyframe for
+    pub sim_change_tick: Tick,     // Change tick replay_keyframe_system last ran at, which snapshots are measured from
+    recorded_inputs: VecDeque<(u32, ReplayInput)>,
+    recorded_keyframes: VecDeque<(u32, u64)>,
+    writer: Option<BufWriter<File>>,
+}
+
+impl Replay {
+    pub fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        if let Some(pair) = args.windows(2).find(|pair| pair[0] == "--replay") {
+            let mut replay = Self::load(&pair[1]).unwrap_or_else(|error| panic!("Could not load replay {}: {}", pair[1], error));
+            if let Some(seek) = args.windows(2).find(|pair| pair[0] == "--seek") {
+                match seek[1].parse() {
+                    Ok(tick) => replay.pending_seek = Some(tick),
+                    Err(_) => warn!("--seek expects a tick, got {}", seek[1]),
+                }
+            }
+            return replay;
+        }
+        let mode = if args.iter().any(|arg| arg == "--record-replay") { ReplayMode::Recording } else { ReplayMode::Off };
+        Self { mode, ..Self::default() }
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+        let mut replay = Self { mode: ReplayMode::Playing, ..Self::default() };
+        for (number, line) in contents.lines().enumerate() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let parsed = match words.as_slice() {
+                [] => Some(()),
+                ["seed", seed] => seed.parse().ok().map(|seed| replay.seed = Some(seed)),
+                ["preset", name] => WorldPreset::from_name(name).map(|preset| replay.preset = Some(preset)),
+                ["utility", scorer @ ..] => UtilityConfig::parse_scorer(scorer).map(|scorer| {
+                    replay.utility.get_or_insert_with(|| UtilityConfig { scorers: Vec::new() }).scorers.push(scorer)
+                }),
+                ["input", tick, rest @ ..] => tick.parse().ok()
+                    .zip(ReplayInput::from_words(rest))
+                    .map(|entry| replay.inputs.push_back(entry)),
+                ["keyframe", tick, checksum] => tick.parse().ok()
+                    .zip(checksum.parse().ok())
+                    .map(|entry| replay.keyframes.push_back(entry)),
+                _ => None,
+            };
+            if parsed.is_none() {
+                return Err(format!("line {} not understood: {}", number + 1, line));
+            }
+        }
+        if replay.seed.is_none() {
+            return Err("no seed".to_string());
+        }
+        let last_input = replay.inputs.back().map_or(0, |&(tick, _)| tick);
+        let last_keyframe = replay.keyframes.back().map_or(0, |&(tick, _)| tick);
+        replay.end_tick = last_input.max(last_keyframe);
+        replay.recorded_inputs = replay.inputs.clone();
+        replay.recorded_keyframes = replay.keyframes.clone();
+        Ok(replay)
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.mode == ReplayMode::Playing
+    }
+
+    // Starts fast-forwarding to a tick; a tick already passed needs the world restarted first
+    pub fn seek(&mut self, target: u32, current_tick: u32) {
+        if target < current_tick {
+            self.rewind_requested = true;
+        } else if target == current_tick {
+            return;
+        }
+        self.seek_target = Some(target);
+    }
+
+    // Plays the recording again from its first input, for a world restarted from the seed
+    pub fn rewind(&mut self) {
+        self.inputs = self.recorded_inputs.clone();
+        self.keyframes = self.recorded_keyframes.clone();
+        self.desynced = false;
+        self.rewind_requested = false;
+    }
+
+    // Picks playback up where a world snapshot left it
+    pub fn resume(&mut self, snapshot: &WorldSnapshot) {
+        self.inputs = snapshot.inputs.clone();
+        self.keyframes = snapshot.keyframes.clone();
+        self.desynced = snapshot.desynced;
+        self.rewind_requested = false;
+    }
+
+    pub fn file_name(seed: u32) -> String {
+        format!("replay_{}.txt", seed)
+    }
+
+    // Opens the replay file and writes the header; called once the world seed is known
+    pub fn start_recording(&mut self, seed: u32, preset: WorldPreset, utility: &UtilityConfig) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(Self::file_name(seed))?);
+        writeln!(writer, "seed {}", seed)?;
+        writeln!(writer, "preset {}", format!("{:?}", preset).to_ascii_lowercase())?;
+        for line in utility.to_lines() {
+            writeln!(writer, "utility {}", line)?;
+        }
+        writer.flush()?;
+        self.writer = Some(writer);
+        Ok(())
+    }
+
+    pub fn record(&mut self, tick: u32, input: ReplayInput) {
+        self.write_line(format!("input {} {}", tick, input.to_words()));
+    }
+
+    pub fn record_keyframe(&mut self, tick: u32, checksum: u64) {
+        self.write_line(format!("keyframe {} {}", tick, checksum));
+    }
+
+    // Flushed every line so a crash still leaves a usable replay for the bug report
+    fn write_line(&mut self, line: String) {
+        let Some(writer) = self.writer.as_mut() else {
+            return;
+        };
+        if let Err(error) = writeln!(writer, "{}", line).and_then(|_| writer.flush()) {
+            warn!("Could not write to replay file, recording stopped: {}", error);
+            self.writer = None;
+        }
+    }
+}
+
+// A resource as a snapshot keeps it, with the change ticks it had
+pub struct SavedResource<R> {
+    pub value: R,
+    pub ticks: ComponentTicks,
+}
+
+// The simulation as it stood at the end of a tick during playback. Creatures and plants are kept as
+// Disabled copies in the world, listed in the order their tables held them, with the change ticks of
+// each component; restoring them in that order, with those ticks, lets the ticks that follow run
+// exactly as they did the first time
+pub struct WorldSnapshot {
+    pub tick: u32,
+    pub change_tick: Tick, // Replay::sim_change_tick as the snapshot was taken
+    pub entities: Vec<(Entity, Vec<(ComponentId, ComponentTicks)>)>,
+    pub tile_events: Vec<Position>, // TileChanged sent during the tick, which the next tick reads
+    pub inputs: VecDeque<(u32, ReplayInput)>,
+    pub keyframes: VecDeque<(u32, u64)>,
+    pub desynced: bool,
+    pub game_grid: SavedResource<GameGrid>,
+    pub nav_graph: SavedResource<NavGraph>,
+    pub tick_count: SavedResource<TickCount>,
+    pub calendar: SavedResource<Calendar>,
+    pub time_of_day: SavedResource<TimeOfDay>,
+    pub plants_fruiting: SavedResource<PlantsFruiting>,
+    pub population: SavedResource<PopulationCount>,
+    pub band_center: SavedResource<BandCenter>,
+    pub band_center_mode: SavedResource<BandCenterMode>,
+    pub band_flow_field: SavedResource<BandFlowField>,
+    pub band_food_memory: SavedResource<BandFoodMemory>,
+    pub in_camp: SavedResource<CreaturesInCamp>,
+    pub manual_band_moves: SavedResource<ManualBandMoves>,
+    pub foot_traffic: SavedResource<FootTraffic>,
+    pub search_budget: SavedResource<PathSearchBudget>,
+    pub weather: SavedResource<Weather>,
+    pub technologies: SavedResource<Technologies>,
+    pub sim_rng: SavedResource<SimRng>,
+    pub next_creature_id: SavedResource<NextCreatureId>,
+    pub genealogy: SavedResource<Genealogy>,
+    // What the panels showed, so they pick up from the same point
+    pub history: History,
+    pub event_log: VecDeque<LoggedEvent>,
+    pub fog: FogOfWar,
+}
+
+// Snapshots taken during playback, oldest first, so a seek can resume from the nearest one instead
+// of re-simulating from the first tick. One is taken every `interval` ticks; each holds a copy of
+// the whole map, so once there are REPLAY_MAX_SNAPSHOTS every other one is dropped and the interval
+// doubles
+#[derive(Resource)]
+pub struct ReplaySnapshots {
+    pub snapshots: Vec<WorldSnapshot>,
+    pub interval: u32,
+    pub tile_cursor: EventCursor<TileChanged>, // TileChanged already looked at for the next snapshot
+}
+
+impl Default for ReplaySnapshots {
+    fn default() -> Self {
+        Self { snapshots: Vec::new(), interval: REPLAY_KEYFRAME_INTERVAL, tile_cursor: EventCursor::default() }
+    }
+}
+
+impl ReplaySnapshots {
+    pub fn is_due(&self, tick: u32) -> bool {
+        tick > 0
+            && tick.is_multiple_of(self.interval)
+            && self.snapshots.binary_search_by_key(&tick, |snapshot| snapshot.tick).is_err()
+    }
+
+    // The latest snapshot at or before a tick
+    pub fn latest_at(&self, tick: u32) -> Option<&WorldSnapshot> {
+        self.snapshots.iter().rev().find(|snapshot| snapshot.tick <= tick)
+    }
+
+    // Adds a snapshot in tick order; returns the copies of any snapshots dropped to make room
+    pub fn insert(&mut self, snapshot: WorldSnapshot) -> Vec<Entity> {
+        let index = self.snapshots.partition_point(|existing| existing.tick < snapshot.tick);
+        self.snapshots.insert(index, snapshot);
+        let mut dropped = Vec::new();
+        if self.snapshots.len() > REPLAY_MAX_SNAPSHOTS {
+            self.interval *= 2;
+            let interval = self.interval;
+            for snapshot in self.snapshots.extract_if(.., |snapshot| !snapshot.tick.is_multiple_of(interval)) {
+                dropped.extend(snapshot.entities.into_iter().map(|(copy, _)| copy));
+            }
+        }
+        dropped
+    }
+}
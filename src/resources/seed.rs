@@ -1,4 +1,9 @@
 use bevy::prelude::Resource;
-    
+use rand_pcg::Pcg32;
+
 #[derive(Resource, Default)]
-pub struct WorldSeed(pub u32);
\ No newline at end of file
+pub struct WorldSeed(pub u32);
+
+// Randomness for the tick systems, seeded from the world seed so a run can be re-simulated
+#[derive(Resource, Clone)]
+pub struct SimRng(pub Pcg32);
\ No newline at end of file
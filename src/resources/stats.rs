@@ -0,0 +1,175 @@
+use bevy::prelude::{Resource, warn};
+use std::fs::File;
+use std::io::{BufWriter, ErrorKind};
+use crate::components::components::DeathCause;
+use crate::constants::*;
+
+// A measurement the stats recorder can sample; some expand into several columns
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatsMetric {
+    Population,
+    Band,           // Members inside the band radius and stragglers outside it
+    Plants,
+    Births,
+    Deaths,         // One column per DeathCause
+    Calories,       // Average over living creatures
+    TilesTraversed,
+    PathFailures,
+}
+
+impl StatsMetric {
+    pub const ALL: [StatsMetric; 8] = [
+        Self::Population,
+        Self::Band,
+        Self::Plants,
+        Self::Births,
+        Self::Deaths,
+        Self::Calories,
+        Self::TilesTraversed,
+        Self::PathFailures,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "population" => Some(Self::Population),
+            "band" => Some(Self::Band),
+            "plants" => Some(Self::Plants),
+            "births" => Some(Self::Births),
+            "deaths" => Some(Self::Deaths),
+            "calories" => Some(Self::Calories),
+            "traversed" | "tiles-traversed" => Some(Self::TilesTraversed),
+            "path-failures" => Some(Self::PathFailures),
+            _ => None,
+        }
+    }
+
+    pub fn columns(&self) -> Vec<String> {
+        match self {
+            Self::Population => vec!["population".into()],
+            Self::Band => vec!["band_members".into(), "stragglers".into()],
+            Self::Plants => vec!["plants".into()],
+            Self::Births => vec!["births".into()],
+            Self::Deaths => DeathCause::ALL.iter()
+                .map(|cause| format!("deaths_{}", cause.label().to_ascii_lowercase()))
+                .collect(),
+            Self::Calories => vec!["average_calories".into()],
+            Self::TilesTraversed => vec!["tiles_traversed".into()],
+            Self::PathFailures => vec!["path_failures".into()],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatsFormat {
+    Csv,
+    JsonLines,
+}
+
+impl StatsFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "jsonl" | "json-lines" => Some(Self::JsonLines),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::JsonLines => "jsonl",
+        }
+    }
+
+    // Files are named after the world seed so runs can be told apart. Another run on the same seed,
+    // such as a replay, gets the next free numbered name rather than overwriting the first one's stats
+    pub fn create_file(&self, seed: u32) -> std::io::Result<(String, File)> {
+        let mut run = 1;
+        loop {
+            let file_name = match run {
+                1 => format!("stats_{}.{}", seed, self.extension()),
+                _ => format!("stats_{}_{}.{}", seed, run, self.extension()),
+            };
+            match File::create_new(&file_name) {
+                Ok(file) => return Ok((file_name, file)),
+                Err(error) if error.kind() == ErrorKind::AlreadyExists => run += 1,
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
+
+// What the stats recorder samples and where, set from the command line:
+// --stats <csv|jsonl> turns it on, --stats-interval <ticks> sets how often a row is written,
+// and --stats-metrics <name,name,...> picks the metrics (all of them by default)
+#[derive(Resource, Clone, Debug)]
+pub struct StatsConfig {
+    pub format: Option<StatsFormat>, // None when recording is off
+    pub interval: u32,
+    pub metrics: Vec<StatsMetric>,
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        Self { format: None, interval: STATS_INTERVAL_TICKS, metrics: StatsMetric::ALL.to_vec() }
+    }
+}
+
+impl StatsConfig {
+    pub fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        let value_of = |flag: &str| args.windows(2).find(|pair| pair[0] == flag).map(|pair| pair[1].clone());
+        let format = value_of("--stats").and_then(|name| {
+            let format = StatsFormat::from_name(&name);
+            if format.is_none() {
+                warn!("Unknown stats format {}, expected csv or jsonl; stats recording is off", name);
+            }
+            format
+        });
+        let mut config = Self { format, ..Self::default() };
+        if let Some(value) = value_of("--stats-interval") {
+            match value.parse() {
+                Ok(interval) => config.interval = u32::max(interval, 1),
+                Err(_) => warn!("Stats interval {} isn't a number of ticks, using {}", value, config.interval),
+            }
+        }
+        if let Some(names) = value_of("--stats-metrics") {
+            let metrics: Vec<StatsMetric> = names.split(',')
+                .filter_map(|name| {
+                    let metric = StatsMetric::from_name(name);
+                    if metric.is_none() {
+                        warn!("Unknown stats metric {}, skipped", name);
+                    }
+                    metric
+                })
+                .collect();
+            if metrics.is_empty() {
+                warn!("No known stats metrics in {}, recording all of them", names);
+            } else {
+                config.metrics = metrics;
+            }
+        }
+        config
+    }
+}
+
+// Counts of what happened since the last row, and the open output file
+#[derive(Resource, Default)]
+pub struct StatsRecorder {
+    pub writer: Option<BufWriter<File>>,
+    pub file_name: String,
+    pub failed: bool, // The file couldn't be written; recording has stopped
+    pub births: u32,
+    pub deaths: [u32; DeathCause::ALL.len()], // Indexed like DeathCause::ALL
+    pub tiles_traversed: u32,
+    pub path_failures: u32,
+}
+
+impl StatsRecorder {
+    pub fn reset_counts(&mut self) {
+        self.births = 0;
+        self.deaths = [0; DeathCause::ALL.len()];
+        self.tiles_traversed = 0;
+        self.path_failures = 0;
+    }
+}
@@ -0,0 +1,32 @@
+use bevy::prelude::Resource;
+use std::collections::HashSet;
+use crate::constants::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Technology {
+    Fire,
+    Rafts,
+}
+
+impl Technology {
+    pub const ALL: [Technology; 2] = [Technology::Fire, Technology::Rafts];
+
+    // Daily odds of discovery per band member
+    pub fn discovery_chance(&self) -> f32 {
+        match self {
+            Technology::Fire => FIRE_DISCOVERY_CHANCE,
+            Technology::Rafts => RAFT_DISCOVERY_CHANCE,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Default, Debug)]
+pub struct Technologies {
+    pub known: HashSet<Technology>,
+}
+
+impl Technologies {
+    pub fn has(&self, technology: Technology) -> bool {
+        self.known.contains(&technology)
+    }
+}
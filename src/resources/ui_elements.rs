@@ -1,20 +1,110 @@
-use bevy::prelude::{Resource, Entity};
+use bevy::prelude::{Resource, Entity, Vec2};
+use crate::components::components::{Position, PanelSection};
+use crate::constants::*;
 
-#[derive(Resource, Default)]
+#[derive(Resource, Clone, Default)]
 pub struct TickCount(pub u32);
 
-#[derive(Resource, Default)]
+#[derive(Resource, Clone, Default)]
 pub struct PopulationCount(pub u32);
 
 #[derive(Resource, Default)]
 pub struct BandCenterVisualizationEnabled(pub bool);
 
-#[derive(Resource, Debug, Clone, Copy)]
+#[derive(Resource, Debug, Clone, Default)]
 pub enum LeftPanelState {
+    #[default]
     None,
     Creature(Entity),
+    Group(Vec<Entity>), // Several creatures selected at once
+    Plant(Entity),
+    Tile(Position),
+    Band,
+}
+
+impl LeftPanelState {
+    // Shows a single creature on its own and several as a group
+    pub fn from_creatures(mut creatures: Vec<Entity>) -> Self {
+        match creatures.len() {
+            0 => Self::None,
+            1 => Self::Creature(creatures.remove(0)),
+            _ => Self::Group(creatures),
+        }
+    }
+
+    // The creatures orders apply to
+    pub fn selected_creatures(&self) -> &[Entity] {
+        match self {
+            Self::Creature(entity) => std::slice::from_ref(entity),
+            Self::Group(entities) => entities,
+            _ => &[],
+        }
+    }
+
+    // The part of the left panel to show, if any
+    pub fn section(&self) -> Option<PanelSection> {
+        match self {
+            Self::None => None,
+            Self::Creature(_) | Self::Group(_) => Some(PanelSection::Creature),
+            Self::Plant(_) => Some(PanelSection::Plant),
+            Self::Tile(_) => Some(PanelSection::Tile),
+            Self::Band => Some(PanelSection::Band),
+        }
+    }
 }
 
-impl Default for LeftPanelState {
-    fn default() -> Self { Self::None }
-}
\ No newline at end of file
+// World position a left-button drag started at, while the button is held
+#[derive(Resource, Default)]
+pub struct DragSelection(pub Option<Vec2>);
+
+// Selections saved to the number keys
+#[derive(Resource, Default)]
+pub struct ControlGroups(pub [Vec<Entity>; 10]);
+
+#[derive(Resource, Default)]
+pub struct ChartsVisible(pub bool);
+
+#[derive(Resource, Default)]
+pub struct EventLogVisible(pub bool);
+
+// How far back the charts reach
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChartWindow {
+    Season,
+    #[default]
+    Year,
+    Decade,
+    All,
+}
+
+impl ChartWindow {
+    pub const ALL: [ChartWindow; 4] = [Self::Season, Self::Year, Self::Decade, Self::All];
+
+    // Length in ticks; None for the whole recorded history
+    pub fn ticks(&self) -> Option<usize> {
+        let season = (TICKS_PER_DAY * DAYS_PER_SEASON) as usize;
+        match self {
+            Self::Season => Some(season),
+            Self::Year => Some(season * 4),
+            Self::Decade => Some(season * 40),
+            Self::All => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Season => "Season",
+            Self::Year => "Year",
+            Self::Decade => "10 years",
+            Self::All => "All",
+        }
+    }
+}
+
+// What the map tiles are colored by
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MapRenderMode {
+    #[default]
+    Terrain,
+    Elevation,
+}
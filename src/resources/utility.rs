@@ -0,0 +1,280 @@
+use bevy::prelude::{Resource, info, warn};
+use crate::components::components::IntentKind;
+use crate::constants::*;
+
+// Normalized [0, 1] facts about a creature that considerations read from
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UtilityInputs {
+    pub hunger: f32,        // 1 - calories ratio
+    pub thirst: f32,        // 1 - hydration ratio
+    pub fatigue: f32,       // 1 - rest ratio
+    pub cold: f32,          // 1 - warmth ratio
+    pub calorie_ratio: f32,
+    pub outside_band: f32,  // 1 when outside the band radius
+    pub not_pregnant: f32,  // 1 when not pregnant
+    pub night: f32,         // 1 when it is too dark to work
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UtilityInput {
+    Hunger,
+    Thirst,
+    Fatigue,
+    Cold,
+    CalorieRatio,
+    OutsideBand,
+    NotPregnant,
+    Night,
+    Constant,
+}
+
+impl UtilityInput {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "hunger" => Some(Self::Hunger),
+            "thirst" => Some(Self::Thirst),
+            "fatigue" => Some(Self::Fatigue),
+            "cold" => Some(Self::Cold),
+            "calorie-ratio" => Some(Self::CalorieRatio),
+            "outside-band" => Some(Self::OutsideBand),
+            "not-pregnant" => Some(Self::NotPregnant),
+            "night" => Some(Self::Night),
+            "constant" => Some(Self::Constant),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Hunger => "hunger",
+            Self::Thirst => "thirst",
+            Self::Fatigue => "fatigue",
+            Self::Cold => "cold",
+            Self::CalorieRatio => "calorie-ratio",
+            Self::OutsideBand => "outside-band",
+            Self::NotPregnant => "not-pregnant",
+            Self::Night => "night",
+            Self::Constant => "constant",
+        }
+    }
+
+    fn read(&self, inputs: &UtilityInputs) -> f32 {
+        match self {
+            Self::Hunger => inputs.hunger,
+            Self::Thirst => inputs.thirst,
+            Self::Fatigue => inputs.fatigue,
+            Self::Cold => inputs.cold,
+            Self::CalorieRatio => inputs.calorie_ratio,
+            Self::OutsideBand => inputs.outside_band,
+            Self::NotPregnant => inputs.not_pregnant,
+            Self::Night => inputs.night,
+            Self::Constant => 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ResponseCurve {
+    Linear,
+    Power(f32),  // Stays low until the input gets large
+    Step(f32),   // 1 at or above the threshold, 0 below
+}
+
+impl ResponseCurve {
+    // "linear", "power:<exponent>" or "step:<threshold>"
+    fn from_name(name: &str) -> Option<Self> {
+        match name.split_once(':') {
+            None if name == "linear" => Some(Self::Linear),
+            Some(("power", exponent)) => exponent.parse().ok().map(Self::Power),
+            Some(("step", threshold)) => threshold.parse().ok().map(Self::Step),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Self::Linear => "linear".to_string(),
+            Self::Power(exponent) => format!("power:{}", exponent),
+            Self::Step(threshold) => format!("step:{}", threshold),
+        }
+    }
+
+    fn apply(&self, x: f32) -> f32 {
+        let x = x.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => x,
+            Self::Power(exponent) => x.powf(*exponent),
+            Self::Step(threshold) => if x >= *threshold { 1.0 } else { 0.0 },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Consideration {
+    pub input: UtilityInput,
+    pub curve: ResponseCurve,
+}
+
+// An intent's score is its weight times the product of its considerations
+#[derive(Clone, Debug)]
+pub struct IntentScorer {
+    pub intent: IntentKind,
+    pub weight: f32,
+    pub considerations: Vec<Consideration>,
+}
+
+fn intent_from_name(name: &str) -> Option<IntentKind> {
+    match name {
+        "return-to-band" => Some(IntentKind::ReturnToBand),
+        "drink" => Some(IntentKind::Drink),
+        "warm" => Some(IntentKind::Warm),
+        "eat" => Some(IntentKind::Eat),
+        "sleep" => Some(IntentKind::Sleep),
+        "procreate" => Some(IntentKind::Procreate),
+        "idle" => Some(IntentKind::Idle),
+        _ => None,
+    }
+}
+
+fn intent_name(intent: IntentKind) -> &'static str {
+    match intent {
+        IntentKind::ReturnToBand => "return-to-band",
+        IntentKind::Drink => "drink",
+        IntentKind::Warm => "warm",
+        IntentKind::Eat => "eat",
+        IntentKind::Sleep => "sleep",
+        IntentKind::Procreate => "procreate",
+        IntentKind::Idle => "idle",
+    }
+}
+
+// The scorers are read from a text file so they can be tuned without a rebuild: the one given with
+// --utility-config <file>, else UTILITY_CONFIG_PATH, else the built-in Default. One scorer per line,
+// "<intent> <weight> <input> <curve> ...", e.g. "eat 1.4 hunger linear"; # starts a comment. An intent
+// listed more than once scores the best of its scorers, so each line is one more reason to want it
+#[derive(Resource, Clone, Debug)]
+pub struct UtilityConfig {
+    pub scorers: Vec<IntentScorer>,
+}
+
+impl UtilityConfig {
+    pub fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        let path = match args.windows(2).find(|pair| pair[0] == "--utility-config") {
+            Some(pair) => pair[1].clone(),
+            None if std::path::Path::new(UTILITY_CONFIG_PATH).exists() => UTILITY_CONFIG_PATH.to_string(),
+            None => return Self::default(),
+        };
+        match Self::load(&path) {
+            Ok(config) => {
+                info!("Loaded {} utility scorers from {}", config.scorers.len(), path);
+                config
+            }
+            Err(error) => {
+                warn!("Could not load utility config {}, using the built-in one: {}", path, error);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+        let mut scorers = Vec::new();
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default();
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words.is_empty() {
+                continue;
+            }
+            let scorer = Self::parse_scorer(&words)
+                .ok_or_else(|| format!("line {} not understood: {}", number + 1, line.trim()))?;
+            scorers.push(scorer);
+        }
+        if scorers.is_empty() {
+            return Err("no scorers".to_string());
+        }
+        Ok(Self { scorers })
+    }
+
+    // Considerations follow the weight as input and curve pairs
+    pub fn parse_scorer(words: &[&str]) -> Option<IntentScorer> {
+        let [intent, weight, considerations @ ..] = words else {
+            return None;
+        };
+        if !considerations.len().is_multiple_of(2) {
+            return None;
+        }
+        let considerations = considerations.chunks(2)
+            .map(|pair| Some(Consideration {
+                input: UtilityInput::from_name(pair[0])?,
+                curve: ResponseCurve::from_name(pair[1])?,
+            }))
+            .collect::<Option<Vec<_>>>()?;
+        Some(IntentScorer { intent: intent_from_name(intent)?, weight: weight.parse().ok()?, considerations })
+    }
+
+    // One line per scorer in the config file's format, which parse_scorer reads back unchanged
+    pub fn to_lines(&self) -> Vec<String> {
+        self.scorers.iter()
+            .map(|scorer| {
+                let mut line = format!("{} {}", intent_name(scorer.intent), scorer.weight);
+                for consideration in &scorer.considerations {
+                    line += &format!(" {} {}", consideration.input.name(), consideration.curve.name());
+                }
+                line
+            })
+            .collect()
+    }
+
+    // Scores every intent once, in the order it first appears in the config
+    pub fn score(&self, inputs: &UtilityInputs) -> Vec<(IntentKind, f32)> {
+        let mut scores: Vec<(IntentKind, f32)> = Vec::new();
+        for scorer in &self.scorers {
+            let score = scorer.considerations.iter()
+                .fold(scorer.weight, |acc, c| acc * c.curve.apply(c.input.read(inputs)));
+            match scores.iter_mut().find(|(intent, _)| *intent == scorer.intent) {
+                Some((_, best)) => *best = best.max(score),
+                None => scores.push((scorer.intent, score)),
+            }
+        }
+        scores
+    }
+}
+
+impl Default for UtilityConfig {
+    fn default() -> Self {
+        use ResponseCurve::*;
+        use UtilityInput::*;
+        let consider = |input, curve| Consideration { input, curve };
+        Self {
+            scorers: vec![
+                IntentScorer { intent: IntentKind::ReturnToBand, weight: 1.0, considerations: vec![
+                    consider(OutsideBand, Step(0.5)),
+                ]},
+                IntentScorer { intent: IntentKind::Drink, weight: 1.6, considerations: vec![
+                    consider(Thirst, Power(2.0)),
+                ]},
+                IntentScorer { intent: IntentKind::Warm, weight: 1.5, considerations: vec![
+                    consider(Cold, Power(2.0)),
+                ]},
+                IntentScorer { intent: IntentKind::Eat, weight: 1.4, considerations: vec![
+                    consider(Hunger, Linear),
+                ]},
+                IntentScorer { intent: IntentKind::Sleep, weight: 0.6, considerations: vec![
+                    consider(Fatigue, Step(0.75)),
+                ]},
+                // Bedtime: only returning to the band or pressing thirst, cold or hunger keep a creature up
+                IntentScorer { intent: IntentKind::Sleep, weight: 0.9, considerations: vec![
+                    consider(Night, Step(0.5)),
+                ]},
+                IntentScorer { intent: IntentKind::Procreate, weight: 0.5, considerations: vec![
+                    consider(CalorieRatio, Step(0.75)),
+                    consider(NotPregnant, Step(0.5)),
+                ]},
+                IntentScorer { intent: IntentKind::Idle, weight: 0.1, considerations: vec![
+                    consider(Constant, Linear),
+                ]},
+            ],
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use bevy::prelude::Resource;
+use rand::Rng;
+use crate::components::components::Position;
+use crate::constants::*;
+use crate::resources::{
+    calendar::Season,
+    game_grid::Tile,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum WeatherKind {
+    #[default]
+    Clear,
+    Rain,
+    Drought,
+}
+
+impl WeatherKind {
+    // Rolls the next spell of weather; spring is wettest, summer driest
+    pub fn roll(season: Season, rng: &mut impl Rng) -> Self {
+        let (rain_chance, drought_chance) = match season {
+            Season::Spring => (0.4, 0.05),
+            Season::Summer => (0.15, 0.3),
+            Season::Autumn => (0.3, 0.1),
+            Season::Winter => (0.2, 0.0),
+        };
+        let roll: f32 = rng.random();
+        if roll < rain_chance {
+            Self::Rain
+        } else if roll < rain_chance + drought_chance {
+            Self::Drought
+        } else {
+            Self::Clear
+        }
+    }
+
+    // Scales how readily plants spread
+    pub fn growth_multiplier(&self) -> f32 {
+        match self {
+            Self::Clear => 1.0,
+            Self::Rain => RAIN_GROWTH_MULTIPLIER,
+            Self::Drought => 0.0,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Debug, Default)]
+pub struct Weather {
+    pub kind: WeatherKind,
+    pub days_remaining: u32,
+    pub harsh_winter: bool,
+    // Tiles under flood water and what they were before, restored when the flood recedes
+    pub flooded_tiles: Vec<(Position, Tile)>,
+    pub flood_days_remaining: u32,
+}
+
+impl Weather {
+    pub fn temperature_offset(&self) -> f32 {
+        if self.harsh_winter { -HARSH_WINTER_TEMPERATURE_DROP } else { 0.0 }
+    }
+
+    pub fn calorie_burn_multiplier(&self) -> f32 {
+        if self.harsh_winter { HARSH_WINTER_BURN_MULTIPLIER } else { 1.0 }
+    }
+}
@@ -0,0 +1,108 @@
+use bevy::prelude::Resource;
+
+// Continents and barriers are described in normalized map coordinates:
+// x and y are fractions of GRID_WIDTH / GRID_HEIGHT in [0, 1].
+
+#[derive(Clone, Copy, Debug)]
+pub struct Continent {
+    pub center: (f32, f32),
+    pub radius: (f32, f32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BarrierKind {
+    Desert,
+    Mountain,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Barrier {
+    pub kind: BarrierKind,
+    pub start: (f32, f32),
+    pub end: (f32, f32),
+    pub width: f32,
+}
+
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub enum WorldPreset {
+    #[default]
+    Noise,       // Unshaped Perlin terrain (original behaviour)
+    Eurasia,     // One wide east-west landmass
+    Americas,    // Two tall north-south landmasses joined by an isthmus
+    Archipelago, // Scattered small islands
+    TwoWorlds,   // Eurasia-like and Americas-like continents side by side
+}
+
+impl WorldPreset {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "noise" => Some(Self::Noise),
+            "eurasia" => Some(Self::Eurasia),
+            "americas" => Some(Self::Americas),
+            "archipelago" => Some(Self::Archipelago),
+            "two-worlds" | "twoworlds" => Some(Self::TwoWorlds),
+            _ => None,
+        }
+    }
+
+    // Reads `--preset <name>` from the command line, falling back to the default
+    pub fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        args.windows(2)
+            .find(|pair| pair[0] == "--preset")
+            .and_then(|pair| Self::from_name(&pair[1]))
+            .unwrap_or_default()
+    }
+
+    // Fixed continents for the preset. Archipelago islands are seeded at generation time.
+    pub fn continents(&self) -> Vec<Continent> {
+        match self {
+            Self::Noise | Self::Archipelago => Vec::new(),
+            Self::Eurasia => vec![
+                Continent { center: (0.5, 0.5), radius: (0.44, 0.24) },
+            ],
+            Self::Americas => vec![
+                Continent { center: (0.48, 0.72), radius: (0.16, 0.24) }, // North
+                Continent { center: (0.52, 0.48), radius: (0.03, 0.08) }, // Isthmus
+                Continent { center: (0.55, 0.25), radius: (0.12, 0.22) }, // South
+            ],
+            Self::TwoWorlds => vec![
+                Continent { center: (0.33, 0.55), radius: (0.28, 0.2) },
+                Continent { center: (0.8, 0.7), radius: (0.09, 0.2) },
+                Continent { center: (0.82, 0.45), radius: (0.02, 0.07) },
+                Continent { center: (0.84, 0.24), radius: (0.07, 0.18) },
+            ],
+        }
+    }
+
+    pub fn barriers(&self) -> Vec<Barrier> {
+        match self {
+            Self::Noise | Self::Archipelago => Vec::new(),
+            // Ranges and deserts mostly run east-west, so they don't block spread along the axis
+            Self::Eurasia => vec![
+                Barrier { kind: BarrierKind::Mountain, start: (0.45, 0.38), end: (0.7, 0.36), width: 0.025 },
+                Barrier { kind: BarrierKind::Desert, start: (0.2, 0.33), end: (0.5, 0.3), width: 0.04 },
+            ],
+            // A western spine and an east-west desert belt split the landmass north from south
+            Self::Americas => vec![
+                Barrier { kind: BarrierKind::Mountain, start: (0.38, 0.88), end: (0.46, 0.1), width: 0.015 },
+                Barrier { kind: BarrierKind::Desert, start: (0.36, 0.57), end: (0.6, 0.57), width: 0.04 },
+            ],
+            Self::TwoWorlds => vec![
+                Barrier { kind: BarrierKind::Mountain, start: (0.25, 0.45), end: (0.45, 0.44), width: 0.02 },
+                Barrier { kind: BarrierKind::Mountain, start: (0.75, 0.85), end: (0.8, 0.1), width: 0.01 },
+                Barrier { kind: BarrierKind::Desert, start: (0.72, 0.6), end: (0.88, 0.6), width: 0.03 },
+            ],
+        }
+    }
+
+    // Where the founding band is placed (normalized coordinates)
+    pub fn spawn_point(&self) -> (f32, f32) {
+        match self {
+            Self::Noise | Self::Archipelago => (0.5, 0.5),
+            Self::Eurasia => (0.5, 0.55),
+            Self::Americas => (0.5, 0.75),
+            Self::TwoWorlds => (0.33, 0.6),
+        }
+    }
+}
@@ -6,57 +6,174 @@ use crate::resources::{
         SpatialGrid,
         GameGrid,
         TileKind,
+        MovementMode,
+        FootTraffic,
     },
-    band_center::{BandCenter, BandCenterMode},
+    band_center::{BandCenter, BandCenterMode, BandFlowField, BandFoodMemory, CreaturesInCamp, ManualBandMoves},
+    ui_elements::TickCount,
+    utility::{UtilityConfig, UtilityInputs},
+    calendar::{Calendar, TimeOfDay},
+    weather::Weather,
+    technology::{Technologies, Technology},
+    nav_graph::{NavGraph, FlowField, PathSearchBudget, chebyshev},
+    stats::StatsRecorder,
+    seed::SimRng,
+    genealogy::NextCreatureId,
 };
 use crate::constants::*;
-use std::collections::HashSet;
-use pathfinding::prelude::astar;
+use std::collections::{HashMap, HashSet};
 
 
-// --- Intent-Driven Systems ---
-pub fn goal_selection_system(
-    mut commands: Commands,
-    creature_query: Query<(Entity, &Calories, &Position), (
-        With<CreatureMarker>,
+// Creatures with no intent, action or order, left for goal selection to decide
+type Undecided = (
+    With<CreatureMarker>,
+    (
         Without<WantsToEat>,
+        Without<WantsToDrink>,
+        Without<WantsToSleep>,
+        Without<WantsToWarm>,
         Without<WantsToIdle>,
         Without<WantsToProcreate>,
         Without<WantsToReturnToBand>,
+        Without<PlayerOrder>,
+    ),
+    (
         Without<ActionTravelTo>,
         Without<ActionEat>,
+        Without<ActionDrink>,
+        Without<ActionSleep>,
+        Without<ActionWarm>,
         Without<ActivePath>,
-        Without<OutsideBandRadius>,
-    )>,
-    pregnant_query: Query<(Entity, &mut Pregnant)>,
+    ),
+    Without<OutsideBandRadius>,
+);
+
+// The four needs goal selection weighs
+type NeedsData = (&'static Calories, &'static Hydration, &'static Rest, &'static BodyWarmth);
+
+// Creatures done travelling, at the place their action happens
+type AtActionSite = (With<CreatureMarker>, Without<ActivePath>, Without<ActionTravelTo>);
+
+// --- Intent-Driven Systems ---
+pub fn goal_selection_system(
+    mut commands: Commands,
+    creature_query: Query<(Entity, NeedsData, &Position, Option<&Pregnant>), Undecided>,
     band_center: Res<BandCenter>,
+    utility_config: Res<UtilityConfig>,
+    time_of_day: Res<TimeOfDay>,
+    technologies: Res<Technologies>,
 ) {
-    for (entity, calories, pos) in creature_query.iter() {
-        let is_hungry = calories.current < (calories.max as f32 * 0.5) as i32;
-        let is_outside_band_radius = is_outside_band_radius(*pos, band_center.0);
-        
+    let is_night = time_of_day.is_night(technologies.has(Technology::Fire));
+    for (entity, (calories, hydration, rest, warmth), pos, pregnant) in creature_query.iter() {
+        let calorie_ratio = calories.current as f32 / calories.max as f32;
+        let inputs = UtilityInputs {
+            hunger: 1.0 - calorie_ratio,
+            thirst: 1.0 - hydration.current as f32 / hydration.max as f32,
+            fatigue: 1.0 - rest.current as f32 / rest.max as f32,
+            cold: 1.0 - warmth.current as f32 / warmth.max as f32,
+            calorie_ratio,
+            outside_band: if is_outside_band_radius(*pos, band_center.0) { 1.0 } else { 0.0 },
+            not_pregnant: if pregnant.is_none() { 1.0 } else { 0.0 },
+            night: if is_night { 1.0 } else { 0.0 },
+        };
 
-        if is_outside_band_radius {
-            commands.entity(entity).insert(WantsToReturnToBand);
-        } else if is_hungry {
-            commands.entity(entity).insert(WantsToEat);
-        } else if !pregnant_query.get(entity).is_ok() && calories.current >= (calories.max as f32 * 0.75) as i32 {
-            commands.entity(entity).insert(WantsToProcreate);
-        } else {
-            commands.entity(entity).insert(WantsToIdle);
+        // Highest score wins; ties go to the intent listed first in the config
+        let scores = utility_config.score(&inputs);
+        let best = scores.iter()
+            .fold(None, |best: Option<(IntentKind, f32)>, &(intent, score)| match best {
+                Some((_, best_score)) if best_score >= score => best,
+                _ => Some((intent, score)),
+            })
+            .map(|(intent, _)| intent)
+            .unwrap_or(IntentKind::Idle);
+
+        let mut entity_commands = commands.entity(entity);
+        match best {
+            IntentKind::ReturnToBand => entity_commands.insert(WantsToReturnToBand),
+            IntentKind::Drink => entity_commands.insert(WantsToDrink),
+            IntentKind::Warm => entity_commands.insert(WantsToWarm),
+            IntentKind::Eat => entity_commands.insert(WantsToEat),
+            IntentKind::Sleep => entity_commands.insert(WantsToSleep),
+            IntentKind::Procreate => entity_commands.insert(WantsToProcreate),
+            IntentKind::Idle => entity_commands.insert(WantsToIdle),
+        };
+        entity_commands.insert(UtilityScores(scores));
+    }
+}
+
+// Turns newly given player orders into actions
+#[allow(clippy::type_complexity)]
+pub fn player_order_system(
+    mut commands: Commands,
+    creature_query: Query<(Entity, &PlayerOrder), (With<CreatureMarker>, Changed<PlayerOrder>)>,
+    plant_query: Query<&Position, EdiblePlant>,
+    band_center: Res<BandCenter>,
+) {
+    for (entity, order) in creature_query.iter() {
+        let mut entity_commands = commands.entity(entity);
+        match *order {
+            PlayerOrder::MoveTo(destination) | PlayerOrder::BuildRaft(destination) => {
+                entity_commands.insert(ActionTravelTo { destination, target: None });
+            }
+            PlayerOrder::ReturnHome => {
+                entity_commands.insert(ActionTravelTo { destination: band_center.0, target: None });
+            }
+            PlayerOrder::Eat(plant) => match plant_query.get(plant) {
+                Ok(plant_pos) => {
+                    entity_commands
+                        .insert(ActionTravelTo { destination: *plant_pos, target: Some(plant) })
+                        .insert(ActionEat { target_entity: plant, progress: 0, max_progress: 3 });
+                }
+                Err(_) => {
+                    entity_commands.remove::<PlayerOrder>();
+                }
+            },
         }
     }
 }
 
+// Orders end once their actions are done or abandoned; a raft order builds the raft on reaching the shore
+#[allow(clippy::type_complexity)]
+pub fn player_order_completion_system(
+    mut commands: Commands,
+    mut creature_query: Query<(Entity, &PlayerOrder, &Position, &mut Calories, Has<Raft>), (
+        With<CreatureMarker>,
+        Without<ActionTravelTo>,
+        Without<ActionEat>,
+        Without<ActivePath>,
+    )>,
+    technologies: Res<Technologies>,
+) {
+    for (entity, order, pos, mut calories, has_raft) in creature_query.iter_mut() {
+        if let PlayerOrder::BuildRaft(shore) = *order
+            && *pos == shore
+            && !has_raft
+            && technologies.has(Technology::Rafts)
+            && calories.current > RAFT_BUILD_COST
+        {
+            calories.current -= RAFT_BUILD_COST;
+            commands.entity(entity).insert(Raft);
+        }
+        commands.entity(entity).remove::<PlayerOrder>();
+    }
+}
+
 pub fn perform_movement_system(
     mut commands: Commands,
     mut query: Query<(Entity, &mut Position, &mut ActivePath, &mut Calories)>,
+    game_grid: Res<GameGrid>,
+    mut traffic: ResMut<FootTraffic>,
+    mut stats: ResMut<StatsRecorder>,
 ) {
     for (entity, mut pos, mut active_path, mut calories) in query.iter_mut() {
         if !active_path.nodes.is_empty() {
             let next_pos = active_path.nodes.remove(0);
             *pos = next_pos;
-            calories.current -= MOVE_COST;
+            traffic.0[next_pos.y as usize][next_pos.x as usize] += 1.0;
+            stats.tiles_traversed += 1;
+            // Worn ground is easier going
+            let surface = game_grid.tiles[next_pos.y as usize][next_pos.x as usize].surface;
+            calories.current -= (MOVE_COST as f32 * surface.calorie_multiplier()) as i32;
         }
         
         if active_path.nodes.is_empty() {
@@ -66,15 +183,23 @@ pub fn perform_movement_system(
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn perform_eat_system(
     mut commands: Commands,
-    mut creature_query: Query<(Entity, &Position, &mut Calories, &mut ActionEat), (With<CreatureMarker>, Without<ActivePath>)>,
-    plant_query: Query<(&Position, &FoodSource), (With<PlantMarker>, With<Harvestable>, With<Edible>, Without<CreatureMarker>)>,
+    mut creature_query: Query<(Entity, &Position, &mut Calories, &mut ActionEat, Has<ActionTravelTo>), (With<CreatureMarker>, Without<ActivePath>)>,
+    plant_query: Query<(&Position, &FoodSource), (EdiblePlant, Without<CreatureMarker>)>,
     being_consumed_query: Query<&BeingConsumed, With<PlantMarker>>,
+    mut game_grid: ResMut<GameGrid>,
+    mut sim_events: EventWriter<SimEvent>,
 ) {
-    for (creature_entity, creature_pos, mut creature_calories, mut eat_action) in creature_query.iter_mut() {
+    for (creature_entity, creature_pos, mut creature_calories, mut eat_action, travelling) in creature_query.iter_mut() {
         if let Ok((plant_pos, plant_food)) = plant_query.get(eat_action.target_entity) {
-            if *creature_pos == *plant_pos {
+            if *creature_pos != *plant_pos {
+                if !travelling {
+                    // The trip failed or ended short of the plant, go back to deciding what to do
+                    commands.entity(creature_entity).remove::<ActionEat>();
+                }
+            } else {
                 // Check if another creature is already consuming this plant
                 if let Ok(being_consumed) = being_consumed_query.get(eat_action.target_entity) {
                     if being_consumed.consumer_entity != creature_entity {
@@ -94,8 +219,12 @@ pub fn perform_eat_system(
                 
                 if eat_action.progress >= eat_action.max_progress {
                     creature_calories.current += plant_food.nutrition_value;
+                    // Every harvest takes something out of the soil
+                    let tile = &mut game_grid.tiles[plant_pos.y as usize][plant_pos.x as usize];
+                    tile.fertility = (tile.fertility - HARVEST_FERTILITY_COST).max(0.0);
                     commands.entity(eat_action.target_entity).despawn();
                     commands.entity(creature_entity).remove::<ActionEat>();
+                    sim_events.write(SimEvent::PlantEaten { creature: creature_entity, position: *plant_pos });
                 }
             }
         } else {
@@ -106,6 +235,73 @@ pub fn perform_eat_system(
     }
 }
 
+pub fn perform_drink_system(
+    mut commands: Commands,
+    mut creature_query: Query<(Entity, &Position, &mut Calories, &mut Hydration, &mut ActionDrink), AtActionSite>,
+) {
+    for (entity, pos, mut calories, mut hydration, mut drink_action) in creature_query.iter_mut() {
+        let source = drink_action.source;
+        if (pos.x - source.x).abs() > 1 || (pos.y - source.y).abs() > 1 {
+            // Couldn't reach the water, go back to deciding what to do
+            commands.entity(entity).remove::<ActionDrink>();
+            continue;
+        }
+
+        drink_action.progress += 1;
+        calories.current -= WORK_COST;
+
+        if drink_action.progress >= drink_action.max_progress {
+            hydration.current = hydration.max;
+            commands.entity(entity).remove::<ActionDrink>();
+        }
+    }
+}
+
+pub fn perform_sleep_system(
+    mut commands: Commands,
+    mut creature_query: Query<(Entity, &mut Rest), (AtActionSite, With<ActionSleep>)>,
+    time_of_day: Res<TimeOfDay>,
+    technologies: Res<Technologies>,
+) {
+    let is_night = time_of_day.is_night(technologies.has(Technology::Fire));
+    for (entity, mut rest) in creature_query.iter_mut() {
+        rest.current = (rest.current + SLEEP_RECOVERY).min(rest.max);
+        // Sleepers stay down until they are rested and it is light again
+        if rest.current >= rest.max && !is_night {
+            commands.entity(entity).remove::<ActionSleep>();
+        }
+    }
+}
+
+pub fn perform_warm_system(
+    mut commands: Commands,
+    mut creature_query: Query<(Entity, &Position, &mut BodyWarmth, &mut ActionWarm), AtActionSite>,
+    others_query: Query<(), With<CreatureMarker>>,
+    spatial_grid: Res<SpatialGrid>,
+) {
+    for (entity, pos, mut warmth, mut warm_action) in creature_query.iter_mut() {
+        // Huddling: each adjacent band member adds body heat
+        let mut huddle_count = 0;
+        for dx in -1i32..=1 {
+            for dy in -1i32..=1 {
+                if let Some(entities) = spatial_grid.0.get(&Position { x: pos.x + dx, y: pos.y + dy }) {
+                    huddle_count += entities.iter()
+                        .filter(|other| **other != entity && others_query.get(**other).is_ok())
+                        .count() as i32;
+                }
+            }
+        }
+
+        warmth.current = (warmth.current + WARMTH_RECOVERY + HUDDLE_WARMTH_BONUS * huddle_count).min(warmth.max);
+        warm_action.progress += 1;
+
+        if warmth.current >= warmth.max || warm_action.progress >= warm_action.max_progress {
+            commands.entity(entity).remove::<ActionWarm>();
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
 pub fn food_target_notification_system(
     mut invalidated_events: EventWriter<FoodTargetInvalidated>,
     newly_consumed_query: Query<(Entity, &BeingConsumed), (With<PlantMarker>, Added<BeingConsumed>)>,
@@ -137,31 +333,140 @@ pub fn handle_food_target_invalidated_system(
     }
 }
 
-pub fn calorie_burn_system(mut query: Query<&mut Calories, With<CreatureMarker>>) {
-    for mut calories in query.iter_mut() {
-        calories.current -= LIVE_COST;
+pub fn calorie_burn_system(
+    mut query: Query<(&mut Calories, Option<&Rest>), With<CreatureMarker>>,
+    calendar: Res<Calendar>,
+    weather: Res<Weather>,
+) {
+    let burn_multiplier = calendar.season.calorie_burn_multiplier() * weather.calorie_burn_multiplier();
+    let live_cost = (LIVE_COST as f32 * burn_multiplier) as i32;
+    for (mut calories, rest) in query.iter_mut() {
+        calories.current -= live_cost;
+        // Exhausted creatures burn extra energy just staying upright
+        if rest.is_some_and(|r| r.current <= 0) {
+            calories.current -= EXHAUSTION_COST;
+        }
+    }
+}
+
+pub fn thirst_system(mut query: Query<&mut Hydration, With<CreatureMarker>>) {
+    for mut hydration in query.iter_mut() {
+        hydration.current -= THIRST_COST;
     }
 }
 
-pub fn death_system(mut commands: Commands, query: Query<(Entity, &Calories)>) {
-    for (entity, calories) in query.iter() {
-        if calories.current <= 0 {
-            commands.entity(entity).despawn(); // now also takes care of despawn child entities
+pub fn fatigue_system(mut query: Query<&mut Rest, (With<CreatureMarker>, Without<ActionSleep>)>) {
+    for mut rest in query.iter_mut() {
+        rest.current = (rest.current - FATIGUE_COST).max(0);
+    }
+}
+
+pub fn body_heat_system(
+    mut query: Query<(&Position, &mut BodyWarmth), With<CreatureMarker>>,
+    game_grid: Res<GameGrid>,
+    calendar: Res<Calendar>,
+    weather: Res<Weather>,
+) {
+    let offset = calendar.season.temperature_offset() + weather.temperature_offset();
+    for (pos, mut warmth) in query.iter_mut() {
+        let temperature = game_grid.temperature_at(*pos) + offset;
+        if temperature < COMFORT_TEMPERATURE {
+            warmth.current -= ((COMFORT_TEMPERATURE - temperature) * COLD_EXPOSURE_RATE).ceil() as i32;
+        } else {
+            warmth.current = (warmth.current + WARMTH_RECOVERY).min(warmth.max);
         }
     }
 }
 
+// Creatures in the water without a raft are swimming: it burns calories fast,
+// and in deep water they may drown
+#[allow(clippy::type_complexity)]
+pub fn swimming_system(
+    mut commands: Commands,
+    mut query: Query<(Entity, &Position, &mut Calories), (With<CreatureMarker>, Without<Raft>)>,
+    game_grid: Res<GameGrid>,
+    mut sim_events: EventWriter<SimEvent>,
+    mut sim_rng: ResMut<SimRng>,
+) {
+    let rng = &mut sim_rng.0;
+    for (entity, pos, mut calories) in query.iter_mut() {
+        let kind = game_grid.tiles[pos.y as usize][pos.x as usize].kind;
+        // Rivers are forded, not swum
+        if !kind.is_water() || kind == TileKind::River {
+            continue;
+        }
+        calories.current -= SWIM_COST;
+        if kind.is_deep_water() && rng.random::<f32>() < DROWNING_CHANCE {
+            commands.entity(entity).despawn();
+            sim_events.write(SimEvent::Death { entity, cause: DeathCause::Drowning, position: *pos });
+        }
+    }
+}
+
+// Once rafts are known, creatures that reach the shore build one and keep it
+#[allow(clippy::type_complexity)]
+pub fn build_raft_system(
+    mut commands: Commands,
+    mut query: Query<(Entity, &Position, &mut Calories), (With<CreatureMarker>, Without<Raft>)>,
+    game_grid: Res<GameGrid>,
+    technologies: Res<Technologies>,
+) {
+    if !technologies.has(Technology::Rafts) {
+        return;
+    }
+    for (entity, pos, mut calories) in query.iter_mut() {
+        let on_shore = (-1..=1).any(|dy| (-1..=1).any(|dx| {
+            let (x, y) = (pos.x + dx, pos.y + dy);
+            x >= 0 && x < GRID_WIDTH as i32 && y >= 0 && y < GRID_HEIGHT as i32 && {
+                let kind = game_grid.tiles[y as usize][x as usize].kind;
+                kind.is_water() && kind != TileKind::River
+            }
+        }));
+        // Only well-fed creatures spare the effort
+        if on_shore && calories.current > RAFT_BUILD_COST * 2 {
+            calories.current -= RAFT_BUILD_COST;
+            commands.entity(entity).insert(Raft);
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn death_system(
+    mut commands: Commands,
+    query: Query<(Entity, &Position, &Calories, Option<&Hydration>, Option<&BodyWarmth>)>,
+    mut sim_events: EventWriter<SimEvent>,
+) {
+    for (entity, pos, calories, hydration, warmth) in query.iter() {
+        let cause = if calories.current <= 0 {
+            DeathCause::Starvation
+        } else if hydration.is_some_and(|h| h.current <= 0) {
+            DeathCause::Thirst
+        } else if warmth.is_some_and(|w| w.current <= 0) {
+            DeathCause::Cold
+        } else {
+            continue;
+        };
+        commands.entity(entity).despawn(); // now also takes care of despawn child entities
+        sim_events.write(SimEvent::Death { entity, cause, position: *pos });
+    }
+}
+
+// Creatures eat what they can see; failing that they head for the nearest patch they
+// remember food in, and with no leads at all they wander off to explore
+#[allow(clippy::type_complexity)]
 pub fn find_food_system(
     mut commands: Commands,
-    creature_query: Query<(Entity, &Position), (With<CreatureMarker>, With<WantsToEat>)>,
-    food_query: Query<(), (With<PlantMarker>, With<Harvestable>, With<Edible>)>,
-    food_pos_query: Query<&Position, (With<PlantMarker>, With<Harvestable>, With<Edible>)>,
+    creature_query: Query<(Entity, &Position, &FoodMemory), (With<CreatureMarker>, With<WantsToEat>)>,
+    food_query: Query<(), EdiblePlant>,
+    food_pos_query: Query<&Position, EdiblePlant>,
     being_consumed_query: Query<(), (With<PlantMarker>, With<BeingConsumed>)>,
     spatial_grid: Res<SpatialGrid>,
+    mut sim_rng: ResMut<SimRng>,
 ) {
     let mut targeted_plants = HashSet::new();
+    let rng = &mut sim_rng.0;
     
-    for (creature_entity, creature_pos) in creature_query.iter() {
+    for (creature_entity, creature_pos, memory) in creature_query.iter() {
         if let Some(food_entity) = find_closest_available_food(&spatial_grid, &food_query, &being_consumed_query, *creature_pos, &targeted_plants) {
             if let Ok(food_pos) = food_pos_query.get(food_entity) {
                 // Mark this plant as targeted
@@ -169,7 +474,7 @@ pub fn find_food_system(
                 
                 commands.entity(creature_entity)
                     .remove::<WantsToEat>()
-                    .insert(ActionTravelTo { destination: *food_pos })
+                    .insert(ActionTravelTo { destination: *food_pos, target: Some(food_entity) })
                     .insert(ActionEat { 
                         target_entity: food_entity,
                         progress: 0,
@@ -177,62 +482,194 @@ pub fn find_food_system(
                     });
             }
         } else {
-            commands.entity(creature_entity).remove::<WantsToEat>();
+            let destination = memory.nearest_food(*creature_pos).unwrap_or_else(|| {
+                let angle = rng.random_range(0.0..std::f32::consts::TAU);
+                Position {
+                    x: (creature_pos.x + (angle.cos() * EXPLORE_DISTANCE as f32) as i32).clamp(0, GRID_WIDTH as i32 - 1),
+                    y: (creature_pos.y + (angle.sin() * EXPLORE_DISTANCE as f32) as i32).clamp(0, GRID_HEIGHT as i32 - 1),
+                }
+            });
+            commands.entity(creature_entity)
+                .remove::<WantsToEat>()
+                .insert(ActionTravelTo { destination, target: None });
         }
     }
 }
 
-pub fn idle_goal_selection_system(
+// Creatures that moved look around and note which nearby patches of land hold food,
+// and which have been picked clean
+#[allow(clippy::type_complexity)]
+pub fn vision_system(
+    mut creature_query: Query<(&Position, &mut FoodMemory), (With<CreatureMarker>, Changed<Position>)>,
+    food_query: Query<(), EdiblePlant>,
+    spatial_grid: Res<SpatialGrid>,
+    tick_count: Res<TickCount>,
+) {
+    for (pos, mut memory) in creature_query.iter_mut() {
+        let mut patches: HashMap<Position, bool> = HashMap::new();
+        for dy in -VISION_RADIUS..=VISION_RADIUS {
+            for dx in -VISION_RADIUS..=VISION_RADIUS {
+                let tile_pos = Position { x: pos.x + dx, y: pos.y + dy };
+                let has_food = spatial_grid.0.get(&tile_pos)
+                    .is_some_and(|entities| entities.iter().any(|entity| food_query.get(*entity).is_ok()));
+                *patches.entry(FoodMemory::patch_of(tile_pos)).or_default() |= has_food;
+            }
+        }
+        for (patch, has_food) in patches {
+            // A patch only counts as empty once all of it is in view
+            let in_full_view = chebyshev(FoodMemory::patch_center(patch), *pos) as i32 + FOOD_PATCH_SIZE / 2 <= VISION_RADIUS;
+            if has_food || in_full_view {
+                memory.record(patch, FoodSighting { tick: tick_count.0, has_food });
+            }
+        }
+    }
+}
+
+// Creatures back at camp pool what they have seen and learn what the rest of the band saw: each one
+// as it arrives, and everyone in camp once a day. Sightings older than FOOD_MEMORY_DAYS are forgotten daily
+pub fn food_memory_sharing_system(
+    mut creature_query: Query<(Entity, &Position, &mut FoodMemory), With<CreatureMarker>>,
+    band_center: Res<BandCenter>,
+    mut band_memory: ResMut<BandFoodMemory>,
+    tick_count: Res<TickCount>,
+    calendar: Res<Calendar>,
+    mut in_camp: ResMut<CreaturesInCamp>,
+) {
+    let new_day = calendar.is_changed();
+    if new_day {
+        let cutoff = tick_count.0.saturating_sub(FOOD_MEMORY_DAYS * TICKS_PER_DAY);
+        band_memory.0.forget_before(cutoff);
+        for (_, _, mut memory) in creature_query.iter_mut() {
+            memory.forget_before(cutoff);
+        }
+    }
+
+    let mut sharing = Vec::new();
+    let mut now_in_camp = HashSet::new();
+    for (entity, pos, memory) in creature_query.iter() {
+        if is_outside_band_radius(*pos, band_center.0) {
+            continue;
+        }
+        now_in_camp.insert(entity);
+        if new_day || !in_camp.0.contains(&entity) {
+            band_memory.0.merge(memory);
+            sharing.push(entity);
+        }
+    }
+    in_camp.0 = now_in_camp;
+    for entity in sharing {
+        if let Ok((_, _, mut memory)) = creature_query.get_mut(entity) {
+            memory.merge(&band_memory.0);
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn find_water_system(
     mut commands: Commands,
-    creature_query: Query<(Entity, &Position, &Calories), (With<CreatureMarker>, With<WantsToIdle>)>,
+    creature_query: Query<(Entity, &Position), (With<CreatureMarker>, With<WantsToDrink>)>,
     game_grid: Res<GameGrid>,
 ) {
-    let mut rng = rand::rng();
-    for (entity, pos, calories) in creature_query.iter() {
-        if calories.current < calories.max {
-            commands.entity(entity).remove::<WantsToIdle>();
-            commands.entity(entity).insert(WantsToEat);
+    for (creature_entity, creature_pos) in creature_query.iter() {
+        if let Some(water_pos) = find_closest_fresh_water(&game_grid, *creature_pos) {
+            commands.entity(creature_entity)
+                .remove::<WantsToDrink>()
+                .insert(ActionTravelTo { destination: drinking_spot(&game_grid, water_pos), target: None })
+                .insert(ActionDrink {
+                    source: water_pos,
+                    progress: 0,
+                    max_progress: DRINK_DURATION,
+                });
         } else {
-            // Try up to 10 times to find a valid non-water destination
-            let mut attempts = 0;
-            let mut found_valid_destination = false;
+            commands.entity(creature_entity).remove::<WantsToDrink>();
+        }
+    }
+}
+
+pub fn fall_asleep_system(
+    mut commands: Commands,
+    creature_query: Query<Entity, (With<CreatureMarker>, With<WantsToSleep>)>,
+    band_center: Res<BandCenter>,
+    time_of_day: Res<TimeOfDay>,
+    technologies: Res<Technologies>,
+) {
+    let is_night = time_of_day.is_night(technologies.has(Technology::Fire));
+    for entity in creature_query.iter() {
+        let mut entity_commands = commands.entity(entity);
+        entity_commands
+            .remove::<WantsToSleep>()
+            .insert(ActionSleep);
+        // At night the band beds down together at its center; daytime naps happen where they stand
+        if is_night {
+            entity_commands.insert(ActionTravelTo { destination: band_center.0, target: None });
+        }
+    }
+}
+
+pub fn seek_warmth_system(
+    mut commands: Commands,
+    creature_query: Query<Entity, (With<CreatureMarker>, With<WantsToWarm>)>,
+    band_center: Res<BandCenter>,
+) {
+    for entity in creature_query.iter() {
+        // Huddle with the band at its center
+        commands.entity(entity)
+            .remove::<WantsToWarm>()
+            .insert(ActionTravelTo { destination: band_center.0, target: None })
+            .insert(ActionWarm { progress: 0, max_progress: WARM_DURATION });
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn idle_goal_selection_system(
+    mut commands: Commands,
+    creature_query: Query<(Entity, &Position), (With<CreatureMarker>, With<WantsToIdle>)>,
+    game_grid: Res<GameGrid>,
+    mut sim_rng: ResMut<SimRng>,
+) {
+    let rng = &mut sim_rng.0;
+    for (entity, pos) in creature_query.iter() {
+        // Try up to 10 times to find a valid non-water destination
+        let mut attempts = 0;
+        let mut found_valid_destination = false;
+        
+        while attempts < 10 && !found_valid_destination {
+            let mut new_pos = *pos;
+            match rng.random_range(0..5) {
+                0 => new_pos.y = (new_pos.y - 1).max(0),
+                1 => new_pos.y = (new_pos.y + 1).min(GRID_HEIGHT as i32 - 1),
+                2 => new_pos.x = (new_pos.x - 1).max(0),
+                3 => new_pos.x = (new_pos.x + 1).min(GRID_WIDTH as i32 - 1),
+                _ => {} // Stay put - always valid
+            }
             
-            while attempts < 10 && !found_valid_destination {
-                let mut new_pos = *pos;
-                match rng.random_range(0..5) {
-                    0 => new_pos.y = (new_pos.y - 1).max(0),
-                    1 => new_pos.y = (new_pos.y + 1).min(GRID_HEIGHT as i32 - 1),
-                    2 => new_pos.x = (new_pos.x - 1).max(0),
-                    3 => new_pos.x = (new_pos.x + 1).min(GRID_WIDTH as i32 - 1),
-                    _ => {} // Stay put - always valid
-                }
-                
-                // Check if the destination is water (if it's not the same position)
-                if new_pos == *pos {
-                    // Staying put is always valid
+            // Check if the destination is water (if it's not the same position)
+            if new_pos == *pos {
+                // Staying put is always valid
+                commands.entity(entity).remove::<WantsToIdle>();
+                found_valid_destination = true;
+            } else {
+                let tile = &game_grid.tiles[new_pos.y as usize][new_pos.x as usize];
+                if !tile.kind.is_water() {
+                    // Non-water tile found, use it
+                    commands.entity(entity)
+                        .remove::<WantsToIdle>()
+                        .insert(ActionTravelTo { destination: new_pos, target: None });
                     found_valid_destination = true;
-                } else {
-                    let tile = &game_grid.tiles[new_pos.y as usize][new_pos.x as usize];
-                    if tile.kind != TileKind::Water {
-                        // Non-water tile found, use it
-                        commands.entity(entity)
-                            .remove::<WantsToIdle>()
-                            .insert(ActionTravelTo { destination: new_pos });
-                        found_valid_destination = true;
-                    }
                 }
-                
-                attempts += 1;
             }
             
-            // If no valid destination found after 10 attempts, just stay put
-            if !found_valid_destination {
-                commands.entity(entity).remove::<WantsToIdle>();
-            }
+            attempts += 1;
+        }
+        
+        // If no valid destination found after 10 attempts, just stay put
+        if !found_valid_destination {
+            commands.entity(entity).remove::<WantsToIdle>();
         }
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn procreation_system(
     mut commands: Commands,
     mut creature_query: Query<(Entity, &mut Calories), (With<CreatureMarker>, With<WantsToProcreate>)>,
@@ -244,11 +681,14 @@ pub fn procreation_system(
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn pregnancy_system(
     mut commands: Commands,
-    mut creature_query: Query<(Entity, &mut Pregnant, &Position), (With<CreatureMarker>, With<Pregnant>)>,
+    mut creature_query: Query<(Entity, &mut Pregnant, &Position, &Lineage), (With<CreatureMarker>, With<Pregnant>)>,
+    mut sim_events: EventWriter<SimEvent>,
+    mut next_creature_id: ResMut<NextCreatureId>,
 ) {
-    for (entity, mut pregnant, pos) in creature_query.iter_mut() {
+    for (entity, mut pregnant, pos, lineage) in creature_query.iter_mut() {
         pregnant.progress += 1;
         if pregnant.progress >= pregnant.max_progress {
             let mut spawn_position = *pos;
@@ -264,54 +704,186 @@ pub fn pregnancy_system(
                 spawn_position = Position { x: pos.x + 1, y: pos.y }; 
             }
 
-            commands.spawn((
+            let child = commands.spawn((
                 CreatureMarker,
+                next_creature_id.take(),
                 Position { x: spawn_position.x, y: spawn_position.y },
-                Calories { current: (HUMAN_MAX_CALORIES / 2) as i32, max: HUMAN_MAX_CALORIES },
-            ));
+                Calories { current: HUMAN_MAX_CALORIES / 2, max: HUMAN_MAX_CALORIES },
+                Hydration { current: HUMAN_MAX_HYDRATION, max: HUMAN_MAX_HYDRATION },
+                Rest { current: HUMAN_MAX_REST, max: HUMAN_MAX_REST },
+                BodyWarmth { current: HUMAN_MAX_WARMTH, max: HUMAN_MAX_WARMTH },
+                FoodMemory::default(),
+                Lineage::child_of(entity, lineage),
+            )).id();
+            sim_events.write(SimEvent::Birth { child, mother: entity, position: spawn_position });
 
             commands.entity(entity).remove::<Pregnant>();
         }
     }
 }
 
-// A* pathfinding system that calculates optimal paths using the game grid
+// Turns travel actions into paths. Trips to the band center follow its flow field;
+// other searches are limited per tick to avoid frame spikes, and the rest keep
+// their ActionTravelTo and are picked up on later ticks
+#[allow(clippy::too_many_arguments)]
 pub fn pathfinding_system(
     mut commands: Commands,
-    query: Query<(Entity, &Position, &ActionTravelTo), Without<ActivePath>>,
+    query: Query<(Entity, &Position, &ActionTravelTo, Has<Raft>), Without<ActivePath>>,
     game_grid: Res<GameGrid>,
+    mut nav_graph: ResMut<NavGraph>,
+    mut search_budget: ResMut<PathSearchBudget>,
+    band_flow_field: Res<BandFlowField>,
+    mut stats: ResMut<StatsRecorder>,
+    mut sim_events: EventWriter<SimEvent>,
 ) {
-    for (entity, current_pos, travel_action) in query.iter() {
-        let destination = travel_action.destination;
+    for (entity, current_pos, travel_action, has_raft) in query.iter() {
+        let mode = if has_raft { MovementMode::Boat } else { MovementMode::Walk };
+        // Destinations that can't be entered, such as a band center out on the water,
+        // move to the nearest tile that can
+        let Some(destination) = game_grid.nearest_enterable(travel_action.destination, mode) else {
+            commands.entity(entity).remove::<ActionTravelTo>();
+            stats.path_failures += 1;
+            sim_events.write(SimEvent::PathFailed { creature: entity, from: *current_pos, to: travel_action.destination });
+            continue;
+        };
         
         if *current_pos == destination {
             commands.entity(entity).remove::<ActionTravelTo>();
-        } else {
-            // Calculate A* path from current position to destination
-            if let Some(path) = calculate_astar_path(*current_pos, destination, &game_grid) {
-                // Remove the first position (current position) from the path
-                let mut nodes = path;
-                if !nodes.is_empty() && nodes[0] == *current_pos {
-                    nodes.remove(0);
-                }
-                
-                // If we have valid moves, create ActivePath
-                if !nodes.is_empty() {
-                    commands.entity(entity).insert(ActivePath { nodes });
-                } else {
-                    // Already at destination
-                    commands.entity(entity).remove::<ActionTravelTo>();
-                }
+            continue;
+        }
+
+        // The band's flow field is walked on foot only
+        let flow_path = band_flow_field.0.as_ref()
+            .filter(|field| !has_raft && field.serves(destination))
+            .and_then(|field| field.path_to(&game_grid, *current_pos, destination));
+        let path = match flow_path {
+            Some(path) => Some(path),
+            None if search_budget.take() => {
+                // Hierarchical search from current position to destination
+                nav_graph.find_path(&game_grid, *current_pos, destination, mode)
+            }
+            None => continue,
+        };
+
+        if let Some(path) = path {
+            // Remove the first position (current position) from the path
+            let mut nodes = path;
+            if !nodes.is_empty() && nodes[0] == *current_pos {
+                nodes.remove(0);
+            }
+            
+            // If we have valid moves, create ActivePath
+            if !nodes.is_empty() {
+                commands.entity(entity).insert(ActivePath { nodes });
             } else {
-                // No path found, remove travel intent
-                // This could happen if destination is unreachable (surrounded by water, etc.)
+                // Already at destination
                 commands.entity(entity).remove::<ActionTravelTo>();
-                warn!("No path found from {:?} to {:?}", current_pos, destination);
             }
+        } else {
+            // No path found, remove travel intent
+            // This could happen if destination is unreachable (surrounded by water, etc.)
+            commands.entity(entity).remove::<ActionTravelTo>();
+            stats.path_failures += 1;
+            sim_events.write(SimEvent::PathFailed { creature: entity, from: *current_pos, to: destination });
         }
     }
 }
 
+// Checks routes against the world each tick: a followed target that moved or vanished,
+// or tiles along the way that can no longer be entered, trigger a replan of the affected stretch
+#[allow(clippy::type_complexity)]
+pub fn path_validation_system(
+    mut commands: Commands,
+    mut creature_query: Query<(Entity, &Position, &mut ActionTravelTo, &mut ActivePath, Has<Raft>), With<CreatureMarker>>,
+    target_query: Query<&Position>,
+    game_grid: Res<GameGrid>,
+    mut nav_graph: ResMut<NavGraph>,
+    mut search_budget: ResMut<PathSearchBudget>,
+    mut tile_events: EventReader<TileChanged>,
+) {
+    let changed_tiles: HashSet<Position> = tile_events.read().map(|event| event.position).collect();
+    let terrain_changed = !changed_tiles.is_empty();
+
+    for (entity, pos, mut travel_action, mut active_path, has_raft) in creature_query.iter_mut() {
+        let mode = if has_raft { MovementMode::Boat } else { MovementMode::Walk };
+        // Impassable nodes, and nodes a terrain change turned into open water: a flooded floodplain
+        // can still be swum, but a route planned over dry land shouldn't wade into the lake
+        let is_blocked = |node: &Position| match game_grid.step_cost(*node, mode) {
+            None => true,
+            Some(cost) => cost >= NAV_WATER_COST && changed_tiles.contains(node),
+        };
+
+        // --- Moving targets ---
+        if let Some(target) = travel_action.target {
+            let Ok(target_pos) = target_query.get(target) else {
+                // Target is gone; the action waiting on it will notice and give up
+                commands.entity(entity).remove::<ActionTravelTo>().remove::<ActivePath>();
+                continue;
+            };
+            travel_action.destination = *target_pos;
+            let path_end = active_path.nodes.last().copied().unwrap_or(*pos);
+            if chebyshev(path_end, *target_pos) > TARGET_REPLAN_DISTANCE as u32 {
+                // pathfinding_system plans a fresh route to where the target is now
+                commands.entity(entity).remove::<ActivePath>();
+                continue;
+            }
+        }
+
+        // --- Changed terrain ---
+        // Without terrain changes only the next step can have become impassable
+        let scan_len = if terrain_changed { active_path.nodes.len() } else { active_path.nodes.len().min(1) };
+        let Some(blocked) = active_path.nodes[..scan_len].iter().position(is_blocked) else {
+            continue;
+        };
+        if !search_budget.take() {
+            // Out of searches this tick: a blocked stretch further on is caught again once it is the
+            // next step, a blocked next step leaves the whole trip to pathfinding_system
+            if blocked == 0 {
+                commands.entity(entity).remove::<ActivePath>();
+            }
+            continue;
+        }
+        // Rejoin the old route at the first usable node past the blocked stretch
+        let detour = active_path.nodes[blocked..].iter()
+            .position(|node| !is_blocked(node))
+            .map(|offset| blocked + offset)
+            .and_then(|rejoin| {
+                nav_graph.find_path(&game_grid, *pos, active_path.nodes[rejoin], mode).map(|path| (rejoin, path))
+            });
+        match detour {
+            Some((rejoin, path)) => {
+                let rest = active_path.nodes.split_off(rejoin + 1);
+                active_path.nodes = path.into_iter().skip(1).chain(rest).collect();
+            }
+            // Nowhere to rejoin: plan the whole trip again
+            None => {
+                commands.entity(entity).remove::<ActivePath>();
+            }
+        }
+    }
+}
+
+// Hands out the tick's hierarchical searches, before any system plans a route
+pub fn refill_path_search_budget_system(mut search_budget: ResMut<PathSearchBudget>) {
+    *search_budget = PathSearchBudget::default();
+}
+
+// Keeps the cached search graph in step with floods and other terrain changes, and with the
+// cheaper going of new trails and roads
+pub fn nav_graph_invalidation_system(
+    mut tile_events: EventReader<TileChanged>,
+    mut cost_events: EventReader<TileCostChanged>,
+    game_grid: Res<GameGrid>,
+    mut nav_graph: ResMut<NavGraph>,
+) {
+    for event in tile_events.read() {
+        nav_graph.invalidate(&game_grid, event.position);
+    }
+    for event in cost_events.read() {
+        nav_graph.invalidate_costs(event.position);
+    }
+}
+
 pub fn return_to_band_system(
     mut commands: Commands,
     creature_query: Query<Entity, (With<CreatureMarker>, With<WantsToReturnToBand>)>,
@@ -320,10 +892,11 @@ pub fn return_to_band_system(
     for entity in creature_query.iter() {
         commands.entity(entity).remove::<WantsToReturnToBand>();
         commands.entity(entity).insert(OutsideBandRadius);
-        commands.entity(entity).insert(ActionTravelTo { destination: band_center.0 });
+        commands.entity(entity).insert(ActionTravelTo { destination: band_center.0, target: None });
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn check_if_returned_to_band_system(
     mut commands: Commands,
     creature_query: Query<(Entity, &Position, Option<&ActionTravelTo>), (With<CreatureMarker>, With<OutsideBandRadius>)>,
@@ -350,6 +923,16 @@ pub fn check_if_returned_to_band_system(
     }
 }
 
+// Reports the band center moves made by hand since the last tick
+pub fn manual_band_move_system(
+    mut band_moves: ResMut<ManualBandMoves>,
+    mut sim_events: EventWriter<SimEvent>,
+) {
+    for (from, to) in band_moves.0.drain(..) {
+        sim_events.write(SimEvent::BandCenterSet { from, to });
+    }
+}
+
 pub fn update_band_center_system(
     creature_query: Query<&Position, With<CreatureMarker>>,
     mut band_center: ResMut<BandCenter>,
@@ -379,8 +962,8 @@ pub fn check_manual_band_return_system(
     mut band_center: ResMut<BandCenter>,
     mut band_center_mode: ResMut<BandCenterMode>,
 ) {
-    // Only check when in Manual mode
-    if let BandCenterMode::Manual(manual_position) = *band_center_mode {
+    // Only check while the center is held in place, by the player or a migration
+    if let BandCenterMode::Manual(manual_position) | BandCenterMode::Migrating(manual_position) = *band_center_mode {
         // Check if all creatures are within band radius of the manual position
         let all_creatures_in_band = creature_query.iter().all(|pos| {
             !is_outside_band_radius(*pos, manual_position)
@@ -400,84 +983,120 @@ pub fn check_manual_band_return_system(
             new_band_center.y /= creature_query.iter().count() as i32;
             band_center.0 = new_band_center;
             
-            info!("All creatures returned to manual band center. Switching back to auto mode.");
+            info!("All creatures gathered at the band center. Switching back to auto mode.");
         }
     }
 }
 
-// --- Helper Functions ---
+// Rebuilds the band's shared flow field when its center drifts or the terrain under it changes
+pub fn band_flow_field_system(
+    band_center: Res<BandCenter>,
+    mut band_flow_field: ResMut<BandFlowField>,
+    game_grid: Res<GameGrid>,
+    mut tile_events: EventReader<TileChanged>,
+) {
+    let mut terrain_changed = false;
+    for event in tile_events.read() {
+        if band_flow_field.0.as_ref().is_some_and(|field| field.covers(event.position)) {
+            terrain_changed = true;
+        }
+    }
 
-// A* pathfinding function that uses the game grid for tile costs
-fn calculate_astar_path(
-    start: Position,
-    end: Position,
-    game_grid: &GameGrid,
-) -> Option<Vec<Position>> {
-    let result = astar(
-        &start,
-        |p| {
-            // Generate all possible neighbors (8-directional movement: N, S, E, W, and diagonals)
-            let neighbors = vec![
-                Position { x: p.x + 1, y: p.y },     // E
-                Position { x: p.x - 1, y: p.y },     // W
-                Position { x: p.x, y: p.y + 1 },     // S
-                Position { x: p.x, y: p.y - 1 },     // N
-                Position { x: p.x + 1, y: p.y + 1 }, // SE
-                Position { x: p.x - 1, y: p.y + 1 }, // SW
-                Position { x: p.x + 1, y: p.y - 1 }, // NE
-                Position { x: p.x - 1, y: p.y - 1 }, // NW
-            ];
-
-            neighbors.into_iter()
-                .filter_map(|neighbor_pos| {
-                    // Check if position is within bounds
-                    if neighbor_pos.x < 0 || neighbor_pos.x >= GRID_WIDTH as i32 ||
-                       neighbor_pos.y < 0 || neighbor_pos.y >= GRID_HEIGHT as i32 {
-                        return None;
-                    }
+    // Anchor the field on solid ground; the center itself may lie out on the water
+    let target = game_grid.nearest_enterable(band_center.0, MovementMode::Walk).unwrap_or(band_center.0);
+    let stale = match &band_flow_field.0 {
+        Some(field) => terrain_changed || !field.serves(target),
+        None => true,
+    };
+    if stale {
+        band_flow_field.0 = Some(FlowField::new(&game_grid, target, FLOW_FIELD_RADIUS));
+    }
+}
 
-                    // Get the tile at this position
-                    let tile = &game_grid.tiles[neighbor_pos.y as usize][neighbor_pos.x as usize];
-                    
-                    // Calculate cost based on tile type and move_cost
-                    let cost = match tile.kind {
-                        TileKind::Empty => 10,  // Standard cost for empty tiles
-                        TileKind::Dirt => tile.move_cost as u32,  // Use tile's move_cost
-                        TileKind::Water => {
-                            // Water is very expensive to traverse (simulating need for boats/swimming)
-                            tile.move_cost as u32 * 10
-                        }
-                    };
+// Once a day, a band whose foraging range has run out of food moves its center
+// toward whichever nearby direction has the most edible plants
+#[allow(clippy::too_many_arguments)]
+pub fn band_migration_system(
+    calendar: Res<Calendar>,
+    mut band_center: ResMut<BandCenter>,
+    mut band_center_mode: ResMut<BandCenterMode>,
+    food_query: Query<(), EdiblePlant>,
+    spatial_grid: Res<SpatialGrid>,
+    game_grid: Res<GameGrid>,
+    mut nav_graph: ResMut<NavGraph>,
+    technologies: Res<Technologies>,
+    mut sim_events: EventWriter<SimEvent>,
+) {
+    // Player-set centers take priority, and nothing is edible anywhere in winter
+    if !calendar.is_changed()
+        || !calendar.season.plants_fruiting()
+        || !matches!(*band_center_mode, BandCenterMode::Auto) {
+        return;
+    }
 
-                    // If cost is reasonable, include this neighbor
-                    if cost <= 1000 {  // Prevent unreasonably high costs
-                        Some((neighbor_pos, cost))
-                    } else {
-                        None
-                    }
-                })
-                .collect::<Vec<_>>()
-        },
-        |p| {
-            // Chebyshev distance heuristic for 8-directional grids (admissible when step costs >= 1)
-            (i32::max((p.x - end.x).abs(), (p.y - end.y).abs())) as u32
-        },
-        |p| *p == end  // Success condition
-    );
+    let current = band_center.0;
+    let mode = if technologies.has(Technology::Rafts) { MovementMode::Boat } else { MovementMode::Walk };
+    let nearby_food = count_food_near(&spatial_grid, &food_query, current, BAND_FORAGE_RADIUS);
+    if nearby_food >= FOOD_SCARCITY_THRESHOLD {
+        return;
+    }
 
-    // Extract just the path from the result
-    result.map(|(path, _cost)| path)
+    let best = (0..8)
+        .filter_map(|i| {
+            let angle = i as f32 * std::f32::consts::FRAC_PI_4;
+            let target = Position {
+                x: (current.x + (angle.cos() * MIGRATION_DISTANCE as f32) as i32).clamp(0, GRID_WIDTH as i32 - 1),
+                y: (current.y + (angle.sin() * MIGRATION_DISTANCE as f32) as i32).clamp(0, GRID_HEIGHT as i32 - 1),
+            };
+            if game_grid.tiles[target.y as usize][target.x as usize].kind.is_water() {
+                return None;
+            }
+            // Land across open water is only in reach once the band can build rafts
+            if !nav_graph.reachable(&game_grid, current, target, mode) {
+                return None;
+            }
+            Some((target, count_food_near(&spatial_grid, &food_query, target, BAND_FORAGE_RADIUS)))
+        })
+        .max_by_key(|(_, food)| *food);
+
+    if let Some((target, food)) = best
+        && food > nearby_food
+    {
+        *band_center_mode = BandCenterMode::Migrating(target);
+        band_center.0 = target;
+        debug!("Food is scarce around {:?} ({} plants); {:?} has {}", current, nearby_food, target, food);
+        sim_events.write(SimEvent::BandMigrated { from: current, to: target });
+    }
+}
+
+// --- Helper Functions ---
+
+fn count_food_near(
+    grid: &SpatialGrid,
+    food_query: &Query<(), EdiblePlant>,
+    center: Position,
+    radius: i32,
+) -> usize {
+    let mut count = 0;
+    for dx in -radius..=radius {
+        for dy in -radius..=radius {
+            if let Some(entities) = grid.0.get(&Position { x: center.x + dx, y: center.y + dy }) {
+                count += entities.iter().filter(|entity| food_query.get(**entity).is_ok()).count();
+            }
+        }
+    }
+    count
 }
 
 // Optimized search function using a spatial grid.
 fn find_closest_available_food(
     grid: &Res<SpatialGrid>,
-    food_query: &Query<(), (With<PlantMarker>, With<Harvestable>, With<Edible>)>,
+    food_query: &Query<(), EdiblePlant>,
     being_consumed_query: &Query<(), (With<PlantMarker>, With<BeingConsumed>)>,
     start_pos: Position,
     targeted_plants: &HashSet<Entity>,
 ) -> Option<Entity> {
-    for radius in 0i32..100 {
+    for radius in 0i32..=VISION_RADIUS {
         for dx in -radius..=radius {
             for dy in -radius..=radius {
                 if dx.abs() != radius && dy.abs() != radius {
@@ -501,6 +1120,44 @@ fn find_closest_available_food(
     None
 }
 
+// Ring search outward over the grid for the nearest lake or river tile
+fn find_closest_fresh_water(game_grid: &GameGrid, start_pos: Position) -> Option<Position> {
+    for radius in 0i32..WATER_SEARCH_RADIUS {
+        for dx in -radius..=radius {
+            for dy in -radius..=radius {
+                if dx.abs() != radius && dy.abs() != radius {
+                    continue;
+                }
+
+                let check_pos = Position { x: start_pos.x + dx, y: start_pos.y + dy };
+                if check_pos.x < 0 || check_pos.x >= GRID_WIDTH as i32 ||
+                   check_pos.y < 0 || check_pos.y >= GRID_HEIGHT as i32 {
+                    continue;
+                }
+
+                if game_grid.tiles[check_pos.y as usize][check_pos.x as usize].kind.is_fresh_water() {
+                    return Some(check_pos);
+                }
+            }
+        }
+    }
+    None
+}
+
+// Prefer standing on dry land next to the water rather than wading in
+fn drinking_spot(game_grid: &GameGrid, water_pos: Position) -> Position {
+    for dx in -1i32..=1 {
+        for dy in -1i32..=1 {
+            let pos = Position { x: water_pos.x + dx, y: water_pos.y + dy };
+            if pos.x >= 0 && pos.x < GRID_WIDTH as i32 && pos.y >= 0 && pos.y < GRID_HEIGHT as i32
+                && !game_grid.tiles[pos.y as usize][pos.x as usize].kind.is_water() {
+                return pos;
+            }
+        }
+    }
+    water_pos
+}
+
 pub fn is_outside_band_radius(
     pos: Position,
     band_center: Position,
@@ -1,12 +1,29 @@
 use bevy::prelude::*;
 use rand::Rng;
+use std::collections::HashSet;
 use crate::components::components::*;
 use crate::resources::{
     game_grid::{
         SpatialGrid,
+        GameGrid,
+        TileKind,
+        Surface,
+        FootTraffic,
     },
     ui_elements::{TickCount, PopulationCount},
+    calendar::{Calendar, PlantsFruiting, TimeOfDay},
+    weather::Weather,
+    technology::{Technologies, Technology},
+    history::{History, HistorySample},
+    stats::{StatsConfig, StatsRecorder, StatsFormat, StatsMetric},
+    event_log::{EventLog, LoggedEvent},
+    genealogy::{Genealogy, GenealogyRecord},
+    seed::{WorldSeed, SimRng},
+    band_center::BandCenter,
 };
+use crate::systems::creature::is_outside_band_radius;
+use std::fs::File;
+use std::io::{BufWriter, Write};
 use crate::constants::*;
 
 
@@ -24,6 +41,118 @@ pub fn tick_counter_system(mut tick_count: ResMut<TickCount>) {
     tick_count.0 += 1;
 }
 
+pub fn calendar_system(tick_count: Res<TickCount>, mut calendar: ResMut<Calendar>) {
+    let today = Calendar::from_tick(tick_count.0);
+    // Only write on a new day so change detection means "the date changed"
+    if *calendar != today {
+        *calendar = today;
+    }
+}
+
+pub fn time_of_day_system(tick_count: Res<TickCount>, mut time_of_day: ResMut<TimeOfDay>) {
+    *time_of_day = TimeOfDay::from_tick(tick_count.0);
+}
+
+// Once a day, each undiscovered technology has a chance to be found that grows with population
+pub fn technology_discovery_system(
+    calendar: Res<Calendar>,
+    population_count: Res<PopulationCount>,
+    mut technologies: ResMut<Technologies>,
+    mut sim_events: EventWriter<SimEvent>,
+    mut sim_rng: ResMut<SimRng>,
+) {
+    if !calendar.is_changed() {
+        return;
+    }
+    let rng = &mut sim_rng.0;
+    for technology in Technology::ALL {
+        if technologies.has(technology) {
+            continue;
+        }
+        let chance = technology.discovery_chance() * population_count.0 as f32;
+        if rng.random::<f32>() < chance {
+            technologies.known.insert(technology);
+            sim_events.write(SimEvent::TechnologyDiscovered(technology));
+        }
+    }
+}
+
+// Once a day, fades old footsteps and wears busy land into trails and then roads;
+// neglected roads grow back over, with some slack so surfaces don't flicker
+pub fn trail_formation_system(
+    calendar: Res<Calendar>,
+    mut traffic: ResMut<FootTraffic>,
+    mut game_grid: ResMut<GameGrid>,
+    mut cost_events: EventWriter<TileCostChanged>,
+) {
+    if !calendar.is_changed() {
+        return;
+    }
+    for (y, row) in traffic.0.iter_mut().enumerate() {
+        for (x, footsteps) in row.iter_mut().enumerate() {
+            *footsteps *= TRAFFIC_DECAY;
+            let tile = &mut game_grid.tiles[y][x];
+            if tile.kind.is_water() {
+                continue;
+            }
+            let surface = match tile.surface {
+                _ if *footsteps >= ROAD_TRAFFIC => Surface::Road,
+                Surface::Road if *footsteps >= ROAD_TRAFFIC / 2.0 => Surface::Road,
+                _ if *footsteps >= TRAIL_TRAFFIC => Surface::Trail,
+                Surface::Road | Surface::Trail if *footsteps >= TRAIL_TRAFFIC / 2.0 => Surface::Trail,
+                _ => Surface::Natural,
+            };
+            if surface != tile.surface {
+                tile.surface = surface;
+                cost_events.write(TileCostChanged { position: Position { x: x as i32, y: y as i32 } });
+            }
+        }
+    }
+}
+
+// Once a day, soil with nothing growing on it recovers toward its natural fertility
+pub fn soil_recovery_system(
+    calendar: Res<Calendar>,
+    mut game_grid: ResMut<GameGrid>,
+    plant_query: Query<&Position, With<PlantMarker>>,
+) {
+    if !calendar.is_changed() {
+        return;
+    }
+    let planted: HashSet<Position> = plant_query.iter().copied().collect();
+    for (y, row) in game_grid.tiles.iter_mut().enumerate() {
+        for (x, tile) in row.iter_mut().enumerate() {
+            let natural = tile.kind.natural_fertility();
+            if tile.fertility < natural && !planted.contains(&Position { x: x as i32, y: y as i32 }) {
+                tile.fertility = (tile.fertility + FALLOW_RECOVERY).min(natural);
+            }
+        }
+    }
+}
+
+// Toggles whether plants bear edible grain when the season changes
+pub fn seasonal_plant_system(
+    mut commands: Commands,
+    calendar: Res<Calendar>,
+    mut last_fruiting: ResMut<PlantsFruiting>,
+    plant_query: Query<(Entity, Has<Harvestable>), With<PlantMarker>>,
+) {
+    let fruiting = calendar.season.plants_fruiting();
+    if last_fruiting.0 == Some(fruiting) {
+        return;
+    }
+    last_fruiting.0 = Some(fruiting);
+
+    for (entity, harvestable) in plant_query.iter() {
+        if fruiting && !harvestable {
+            commands.entity(entity).insert(Harvestable);
+        } else if !fruiting && harvestable {
+            commands.entity(entity).remove::<Harvestable>();
+        }
+    }
+    info!("{:?}: plants are {}", calendar.season, if fruiting { "fruiting" } else { "dormant" });
+}
+
 pub fn population_counter_system(
     creature_query: Query<&CreatureMarker>,
     mut population_count: ResMut<PopulationCount>,
@@ -32,13 +161,154 @@ pub fn population_counter_system(
     population_count.0 = population as u32;
 }
 
+// Records this tick's statistics for the charts
+pub fn record_history_system(
+    mut history: ResMut<History>,
+    mut sim_events: EventReader<SimEvent>,
+    creature_query: Query<(&Calories, Has<Pregnant>), With<CreatureMarker>>,
+    plant_query: Query<(), With<PlantMarker>>,
+) {
+    let mut sample = HistorySample {
+        plants: plant_query.iter().count() as u32,
+        ..default()
+    };
+    for event in sim_events.read() {
+        match event {
+            SimEvent::Birth { .. } => sample.births += 1,
+            SimEvent::Death { cause, .. } => sample.deaths[cause.index()] += 1,
+            _ => {}
+        }
+    }
+    let mut total_calories = 0;
+    for (calories, pregnant) in creature_query.iter() {
+        sample.population += 1;
+        total_calories += calories.current.max(0) as i64;
+        if pregnant {
+            sample.pregnant += 1;
+        }
+    }
+    if sample.population > 0 {
+        sample.average_calories = total_calories as f32 / sample.population as f32;
+    }
+    history.push(sample);
+}
+
+// Tallies births and deaths, and every StatsConfig interval writes a row of the chosen metrics to the stats file
+#[allow(clippy::too_many_arguments)]
+pub fn stats_recorder_system(
+    config: Res<StatsConfig>,
+    mut recorder: ResMut<StatsRecorder>,
+    tick_count: Res<TickCount>,
+    world_seed: Res<WorldSeed>,
+    band_center: Res<BandCenter>,
+    mut sim_events: EventReader<SimEvent>,
+    creature_query: Query<(&Position, &Calories), With<CreatureMarker>>,
+    plant_query: Query<(), With<PlantMarker>>,
+) {
+    for event in sim_events.read() {
+        match event {
+            SimEvent::Birth { .. } => recorder.births += 1,
+            SimEvent::Death { cause, .. } => recorder.deaths[cause.index()] += 1,
+            _ => {}
+        }
+    }
+    if !tick_count.0.is_multiple_of(config.interval) {
+        return;
+    }
+    let Some(format) = config.format else {
+        recorder.reset_counts();
+        return;
+    };
+    if recorder.failed {
+        return;
+    }
+
+    let mut columns = vec!["tick".to_string(), "seed".to_string()];
+    let mut values = vec![tick_count.0.to_string(), world_seed.0.to_string()];
+    for metric in &config.metrics {
+        columns.extend(metric.columns());
+        match metric {
+            StatsMetric::Population => values.push(creature_query.iter().count().to_string()),
+            StatsMetric::Band => {
+                let stragglers = creature_query.iter()
+                    .filter(|(pos, _)| is_outside_band_radius(**pos, band_center.0))
+                    .count();
+                values.push((creature_query.iter().count() - stragglers).to_string());
+                values.push(stragglers.to_string());
+            }
+            StatsMetric::Plants => values.push(plant_query.iter().count().to_string()),
+            StatsMetric::Births => values.push(recorder.births.to_string()),
+            StatsMetric::Deaths => values.extend(recorder.deaths.iter().map(|count| count.to_string())),
+            StatsMetric::Calories => {
+                let population = creature_query.iter().count();
+                let total: i64 = creature_query.iter().map(|(_, calories)| calories.current.max(0) as i64).sum();
+                values.push(format!("{:.1}", if population > 0 { total as f32 / population as f32 } else { 0.0 }));
+            }
+            StatsMetric::TilesTraversed => values.push(recorder.tiles_traversed.to_string()),
+            StatsMetric::PathFailures => values.push(recorder.path_failures.to_string()),
+        }
+    }
+    recorder.reset_counts();
+
+    // The file is opened on the first row, once the world seed is known
+    if recorder.writer.is_none() {
+        let mut writer = match format.create_file(world_seed.0) {
+            Ok((file_name, file)) => {
+                recorder.file_name = file_name;
+                BufWriter::new(file)
+            }
+            Err(error) => {
+                warn!("Could not create stats file: {}", error);
+                recorder.failed = true;
+                return;
+            }
+        };
+        if format == StatsFormat::Csv && writeln!(writer, "{}", columns.join(",")).is_err() {
+            recorder.failed = true;
+            return;
+        }
+        info!("Recording stats to {}", recorder.file_name);
+        recorder.writer = Some(writer);
+    }
+
+    let line = match format {
+        StatsFormat::Csv => values.join(","),
+        StatsFormat::JsonLines => {
+            let fields: Vec<String> = columns.iter().zip(&values)
+                .map(|(column, value)| format!("\"{}\":{}", column, value))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+    };
+    // Flushed every row so the file stays usable if the game is closed mid-run
+    if let Some(writer) = recorder.writer.as_mut()
+        && let Err(error) = writeln!(writer, "{}", line).and_then(|_| writer.flush())
+    {
+        warn!("Could not write to stats file {}: {}", recorder.file_name, error);
+        recorder.failed = true;
+        recorder.writer = None;
+    }
+}
+
 pub fn plant_propagation_system(
     mut commands: Commands,
     plant_query: Query<(&Position, &PlantMarker)>,
     grid: Res<SpatialGrid>,
+    mut game_grid: ResMut<GameGrid>,
+    calendar: Res<Calendar>,
+    weather: Res<Weather>,
+    mut sim_rng: ResMut<SimRng>,
 ) {
+    // Plants only spread during the growing season, faster in rain and not at all in drought
+    let growth_multiplier = weather.kind.growth_multiplier();
+    if !calendar.season.plants_growing() || growth_multiplier <= 0.0 {
+        return;
+    }
+    let growth_chance = (PLANT_GROWTH_CHANCE as f32 / growth_multiplier) as u32;
+    let rng = &mut sim_rng.0;
+
     for (pos, plant_marker) in plant_query.iter() {
-        let spawn_plant = rand::rng().random_range(0..100) == 0; // 1% chance
+        let spawn_plant = rng.random_range(0..growth_chance) == 0;
         if spawn_plant {
             let mut empty_neighbors = Vec::new();
             
@@ -53,8 +323,11 @@ pub fn plant_propagation_system(
                            neighbor_y >= 0 && neighbor_y < GRID_HEIGHT as i32 {
                             let neighbor_pos = Position { x: neighbor_x, y: neighbor_y };
                             
-                            // Check if this position is empty (no entities at this position)
-                            if !grid.0.contains_key(&neighbor_pos) {
+                            // Check if this position is empty (no entities at this position) and fertile land
+                            let tile = &game_grid.tiles[neighbor_y as usize][neighbor_x as usize];
+                            if !grid.0.contains_key(&neighbor_pos)
+                                && matches!(tile.kind, TileKind::Dirt | TileKind::Floodplain)
+                                && tile.fertility >= MIN_GROWTH_FERTILITY {
                                 empty_neighbors.push(neighbor_pos);
                             }
                         }
@@ -64,9 +337,16 @@ pub fn plant_propagation_system(
             
             // If there are empty neighbors, pick one at random and spawn a plant there
             if !empty_neighbors.is_empty() {
-                let random_index = rand::rng().random_range(0..empty_neighbors.len());
+                let random_index = rng.random_range(0..empty_neighbors.len());
                 let spawn_pos = empty_neighbors[random_index];
-                
+
+                // Seeds take root more reliably in richer soil, and draw on it as they grow
+                let tile = &mut game_grid.tiles[spawn_pos.y as usize][spawn_pos.x as usize];
+                if rng.random::<f32>() >= tile.fertility {
+                    continue;
+                }
+                tile.fertility = (tile.fertility - PLANT_GROWTH_FERTILITY_COST).max(0.0);
+
                 commands.spawn((
                     PlantMarker { plant_type: plant_marker.plant_type },
                     Position { x: spawn_pos.x, y: spawn_pos.y },
@@ -78,3 +358,109 @@ pub fn plant_propagation_system(
         }
     }
 }
+
+// Keeps the family records: founders when they first appear, then every birth and death
+pub fn genealogy_system(
+    mut genealogy: ResMut<Genealogy>,
+    mut sim_events: EventReader<SimEvent>,
+    founder_query: Query<(Entity, &Lineage, &CreatureId), Added<Lineage>>,
+    id_query: Query<&CreatureId>,
+    tick_count: Res<TickCount>,
+) {
+    for (entity, lineage, &id) in founder_query.iter() {
+        if lineage.mother.is_none() {
+            genealogy.records.entry(entity).or_insert(GenealogyRecord {
+                id,
+                mother: None,
+                founder: lineage.founder,
+                generation: 0,
+                children: Vec::new(),
+                born_tick: tick_count.0,
+                death: None,
+            });
+        }
+    }
+    for event in sim_events.read() {
+        match *event {
+            SimEvent::Birth { child, mother, .. } => {
+                let (Some(mother_record), Ok(&id)) = (genealogy.records.get_mut(&mother), id_query.get(child)) else {
+                    continue;
+                };
+                mother_record.children.push(child);
+                let (founder, generation) = (mother_record.founder, mother_record.generation + 1);
+                genealogy.records.insert(child, GenealogyRecord {
+                    id,
+                    mother: Some(mother),
+                    founder,
+                    generation,
+                    children: Vec::new(),
+                    born_tick: tick_count.0,
+                    death: None,
+                });
+            }
+            SimEvent::Death { entity, cause, .. } => {
+                if let Some(record) = genealogy.records.get_mut(&entity) {
+                    record.death = Some((tick_count.0, cause));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// Stamps this tick's SimEvents with the tick and adds them to the event log, echoing them to the
+// console and, with --event-log, appending them to the events file
+pub fn event_log_system(
+    mut event_log: ResMut<EventLog>,
+    mut sim_events: EventReader<SimEvent>,
+    tick_count: Res<TickCount>,
+    world_seed: Res<WorldSeed>,
+    genealogy: Res<Genealogy>,
+) {
+    let events: Vec<SimEvent> = sim_events.read().copied().collect();
+    if events.is_empty() {
+        return;
+    }
+    let tick = tick_count.0;
+    for &event in &events {
+        match event {
+            SimEvent::PlantEaten { .. } => debug!("Tick {}: {}", tick, event.describe(&genealogy)),
+            SimEvent::PathFailed { .. } => warn!("Tick {}: {}", tick, event.describe(&genealogy)),
+            _ => info!("Tick {}: {}", tick, event.describe(&genealogy)),
+        }
+        event_log.push(LoggedEvent { tick, event });
+    }
+    if !event_log.to_file || event_log.failed {
+        return;
+    }
+
+    let file_name = EventLog::file_name(world_seed.0);
+    if event_log.writer.is_none() {
+        match File::create(&file_name) {
+            Ok(file) => {
+                info!("Recording events to {}", file_name);
+                event_log.writer = Some(BufWriter::new(file));
+            }
+            Err(error) => {
+                warn!("Could not create event log {}: {}", file_name, error);
+                event_log.failed = true;
+                return;
+            }
+        }
+    }
+    let Some(writer) = event_log.writer.as_mut() else {
+        return;
+    };
+    let result = events.iter().try_for_each(|event| {
+        let mut line = format!("{{\"tick\":{},\"event\":\"{}\"", tick, event.name());
+        for (key, value) in event.json_fields(&genealogy) {
+            line.push_str(&format!(",\"{}\":{}", key, value));
+        }
+        writeln!(writer, "{}}}", line)
+    }).and_then(|_| writer.flush());
+    if let Err(error) = result {
+        warn!("Could not write to event log {}: {}", file_name, error);
+        event_log.failed = true;
+        event_log.writer = None;
+    }
+}
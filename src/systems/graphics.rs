@@ -1,13 +1,24 @@
 use bevy::prelude::*;
 use crate::resources::{
-    ui_elements::{TickCount, PopulationCount, LeftPanelState},
-    band_center::BandCenter,
-    ui_elements::BandCenterVisualizationEnabled,
+    ui_elements::{TickCount, PopulationCount, LeftPanelState, MapRenderMode, DragSelection},
+    calendar::{Calendar, TimeOfDay},
+    game_grid::{GameGrid, TileKind, Tile, Surface, MovementMode},
+    weather::Weather,
+    band_center::{BandCenter, BandCenterMode},
+    fog_of_war::{FogOfWar, TileVisibility},
+    game_state::{GameState, SimulationSpeed},
+    ui_elements::{BandCenterVisualizationEnabled, ChartsVisible, ChartWindow, EventLogVisible},
+    event_log::EventLog,
+    genealogy::Genealogy,
+    history::{History, HistorySample},
 };
 use crate::components::components::*;
 use crate::constants::*;
+use crate::systems::{input::cast_cursor_position, creature::is_outside_band_radius};
 use rand::Rng;
+use std::collections::{HashMap, HashSet};
 
+#[allow(clippy::type_complexity)]
 pub fn spawn_creature_visuals_system(
     mut commands: Commands,
     query: Query<(Entity, &Position), (With<CreatureMarker>, Added<Position>)>,
@@ -16,62 +27,73 @@ pub fn spawn_creature_visuals_system(
     let mut rng = rand::rng();
     
     for (entity, pos) in query.iter() {
-        commands.entity(entity).insert(
-            Sprite {
-                color: Color::srgb(0.0, 1.0, 0.0), // Default color
-                custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
-                image: asset_server.load("sprites/human_v2.png"),
-                ..default()
-            }
-        );
-        commands.entity(entity).insert(
-            Transform::from_xyz(
-                pos.x as f32 * TILE_SIZE - (GRID_WIDTH as f32 * TILE_SIZE) / 2.0 + TILE_SIZE / 2.0,
-                pos.y as f32 * TILE_SIZE - (GRID_HEIGHT as f32 * TILE_SIZE) / 2.0 + TILE_SIZE / 2.0,
-                2.0, // Higher Z-index to be on top of tiles
-            )
-        );
-
-        // Create a child entity for the headband
-        let headband_entity = commands.spawn((
-            Sprite {
-                color: HEADBAND_COLORS[rng.random_range(0..HEADBAND_COLORS.len())],
-                custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
-                image: asset_server.load("sprites/human_headband_v2.png"),
-                ..default()
-            },
-            Transform::from_xyz(0.0, 0.0, 0.1), // Relative to parent, slightly higher Z
-        )).id();
-
-        // Make the headband a child of the creature
-        commands.entity(entity).add_child(headband_entity);
+        insert_creature_visuals(&mut commands, entity, *pos, &asset_server, &mut rng);
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn spawn_plant_visuals_system(
     mut commands: Commands,
     query: Query<(Entity, &Position), (With<PlantMarker>, Added<Position>)>,
     asset_server: Res<AssetServer>,
 ) {
     for (entity, pos) in query.iter() {
-        commands.entity(entity).insert(
-            Sprite {
-                color: Color::srgb(0.0, 1.0, 0.0), // Default color
-                custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
-                image: asset_server.load("sprites/wheat.png"),
-                ..default()
-            }
-        );
-        commands.entity(entity).insert(
-            Transform::from_xyz(
-                pos.x as f32 * TILE_SIZE - (GRID_WIDTH as f32 * TILE_SIZE) / 2.0 + TILE_SIZE / 2.0,
-                pos.y as f32 * TILE_SIZE - (GRID_HEIGHT as f32 * TILE_SIZE) / 2.0 + TILE_SIZE / 2.0,
-                1.0, // Higher Z-index to be on top of tiles
-            )
-        );
+        insert_plant_visuals(&mut commands, entity, *pos, &asset_server);
     }
 }
 
+// Sprite and headband for a new creature, or one restored from a replay snapshot
+pub fn insert_creature_visuals(commands: &mut Commands, entity: Entity, pos: Position, asset_server: &AssetServer, rng: &mut impl Rng) {
+    commands.entity(entity).insert(
+        Sprite {
+            color: Color::srgb(0.0, 1.0, 0.0), // Default color
+            custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
+            image: asset_server.load("sprites/human_v2.png"),
+            ..default()
+        }
+    );
+    commands.entity(entity).insert(
+        Transform::from_xyz(
+            pos.x as f32 * TILE_SIZE - (GRID_WIDTH as f32 * TILE_SIZE) / 2.0 + TILE_SIZE / 2.0,
+            pos.y as f32 * TILE_SIZE - (GRID_HEIGHT as f32 * TILE_SIZE) / 2.0 + TILE_SIZE / 2.0,
+            2.0, // Higher Z-index to be on top of tiles
+        )
+    );
+
+    // Create a child entity for the headband
+    let headband_entity = commands.spawn((
+        Sprite {
+            color: HEADBAND_COLORS[rng.random_range(0..HEADBAND_COLORS.len())],
+            custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
+            image: asset_server.load("sprites/human_headband_v2.png"),
+            ..default()
+        },
+        Transform::from_xyz(0.0, 0.0, 0.1), // Relative to parent, slightly higher Z
+    )).id();
+
+    // Make the headband a child of the creature
+    commands.entity(entity).add_child(headband_entity);
+}
+
+// Sprite for a new plant, or one restored from a replay snapshot
+pub fn insert_plant_visuals(commands: &mut Commands, entity: Entity, pos: Position, asset_server: &AssetServer) {
+    commands.entity(entity).insert(
+        Sprite {
+            color: Color::srgb(0.0, 1.0, 0.0), // Default color
+            custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
+            image: asset_server.load("sprites/wheat.png"),
+            ..default()
+        }
+    );
+    commands.entity(entity).insert(
+        Transform::from_xyz(
+            pos.x as f32 * TILE_SIZE - (GRID_WIDTH as f32 * TILE_SIZE) / 2.0 + TILE_SIZE / 2.0,
+            pos.y as f32 * TILE_SIZE - (GRID_HEIGHT as f32 * TILE_SIZE) / 2.0 + TILE_SIZE / 2.0,
+            1.0, // Higher Z-index to be on top of tiles
+        )
+    );
+}
+
 // System to update the visual position of creatures when their grid Position changes
 pub fn update_creature_position_visuals_system(
     mut query: Query<(&mut Transform, &Position), With<CreatureMarker>>,
@@ -97,19 +119,20 @@ pub fn update_creature_color_system(mut query: Query<(&mut Sprite, &Calories), W
     }
 }
 
-// Path visualization system - creates visual markers for active paths
+// Path visualization system - creates visual markers for the selected creatures' active paths
 pub fn path_visualization_system(
     mut commands: Commands,
-    creature_query: Query<(Entity, &ActivePath), (With<CreatureMarker>, With<PathVisualizationEnabled>)>,
+    creature_query: Query<(Entity, &ActivePath), With<CreatureMarker>>,
     existing_path_markers: Query<Entity, With<PathMarker>>,
+    panel_state: Res<LeftPanelState>,
 ) {
     // Clean up existing path markers first
     for marker_entity in existing_path_markers.iter() {
         commands.entity(marker_entity).despawn();
     }
     
-    // Create new path markers for creatures with visualization enabled
-    for (creature_entity, active_path) in creature_query.iter() {
+    // Create new path markers for the selected creatures
+    for (creature_entity, active_path) in creature_query.iter_many(panel_state.selected_creatures()) {
         for (index, &path_node) in active_path.nodes.iter().enumerate() {
             // Calculate world position from grid position
             let world_x = (path_node.x as f32 - GRID_WIDTH as f32 / 2.0) * TILE_SIZE;
@@ -135,15 +158,18 @@ pub fn path_visualization_system(
     }
 }
 
-// Cleanup system to remove path visualization when creatures die or lose ActivePath
+// Cleanup system to remove path visualization when creatures die, lose ActivePath or are deselected
 pub fn cleanup_path_visualization_system(
     mut commands: Commands,
     path_markers: Query<(Entity, &PathMarker)>,
-    creatures_with_paths: Query<(), (With<CreatureMarker>, With<ActivePath>, With<PathVisualizationEnabled>)>,
+    creatures_with_paths: Query<(), (With<CreatureMarker>, With<ActivePath>)>,
+    panel_state: Res<LeftPanelState>,
 ) {
     for (marker_entity, path_marker) in path_markers.iter() {
-        // If the creature no longer exists or doesn't have the required components, remove the marker
-        if creatures_with_paths.get(path_marker.creature_entity).is_err() {
+        // If the creature no longer exists, has no path or isn't selected, remove the marker
+        if creatures_with_paths.get(path_marker.creature_entity).is_err()
+            || !panel_state.selected_creatures().contains(&path_marker.creature_entity)
+        {
             commands.entity(marker_entity).despawn();
         }
     }
@@ -197,15 +223,260 @@ pub fn band_center_visualization_system(
     }
 }
 
+// Recolors tile sprites whose GameGrid tile changed kind or surface after world generation
+#[allow(clippy::too_many_arguments)]
+pub fn update_tile_visuals_system(
+    mut tile_events: EventReader<TileChanged>,
+    mut cost_events: EventReader<TileCostChanged>,
+    mut tile_query: Query<(&Position, &mut Sprite), With<TileMarker>>,
+    game_grid: Res<GameGrid>,
+    render_mode: Res<MapRenderMode>,
+    calendar: Res<Calendar>,
+    fog: Res<FogOfWar>,
+    mut shown_soil: Local<Vec<Vec<u8>>>,
+    mut fog_shown: Local<Option<bool>>,
+) {
+    let mut changed: HashSet<Position> = tile_events.read().map(|event| event.position)
+        .chain(cost_events.read().map(|event| event.position))
+        .collect();
+    // Switching render mode or turning the fog on or off repaints the whole map
+    let repaint_all = render_mode.is_changed() || *fog_shown != Some(fog.enabled);
+    *fog_shown = Some(fog.enabled);
+    if fog.enabled && fog.is_changed() {
+        changed.extend(fog.changed.iter().copied());
+    }
+
+    // Soil fertility drifts without TileChanged events, since it doesn't affect movement;
+    // once a day, repaint the tiles whose visible fertility has moved
+    if shown_soil.is_empty() {
+        *shown_soil = game_grid.tiles.iter().map(|row| row.iter().map(Tile::soil_level).collect()).collect();
+    } else if calendar.is_changed() {
+        for (y, row) in game_grid.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                let level = tile.soil_level();
+                if shown_soil[y][x] != level {
+                    shown_soil[y][x] = level;
+                    if *render_mode == MapRenderMode::Terrain {
+                        changed.insert(Position { x: x as i32, y: y as i32 });
+                    }
+                }
+            }
+        }
+    }
+
+    if changed.is_empty() && !repaint_all {
+        return;
+    }
+
+    for (pos, mut sprite) in tile_query.iter_mut() {
+        if repaint_all || changed.contains(pos) {
+            let tile = &game_grid.tiles[pos.y as usize][pos.x as usize];
+            let color = match *render_mode {
+                MapRenderMode::Terrain => tile_color(tile, pos.x as usize, pos.y as usize),
+                MapRenderMode::Elevation => elevation_color(tile),
+            };
+            sprite.color = match fog.visibility_at(*pos) {
+                TileVisibility::Unexplored => Color::srgb(0.05, 0.05, 0.07),
+                TileVisibility::Explored => color.mix(&Color::BLACK, FOG_EXPLORED_DARKNESS),
+                TileVisibility::Visible => color,
+            };
+        }
+    }
+}
+
+// Recomputes what the band can see whenever one of its creatures moves or dies
+pub fn fog_of_war_system(
+    mut fog: ResMut<FogOfWar>,
+    creature_query: Query<&Position, With<CreatureMarker>>,
+    moved_query: Query<(), (With<CreatureMarker>, Changed<Position>)>,
+    mut removed_creatures: RemovedComponents<CreatureMarker>,
+) {
+    let died = removed_creatures.read().count() > 0;
+    if moved_query.is_empty() && !died {
+        return;
+    }
+    fog.update(creature_query.iter().copied());
+}
+
+// Hides plants outside the band's vision while the fog is on
+pub fn fog_of_war_plant_visibility_system(
+    fog: Res<FogOfWar>,
+    mut plant_query: Query<(&Position, &mut Visibility), With<PlantMarker>>,
+) {
+    let refresh_all = fog.is_changed();
+    for (pos, mut visibility) in plant_query.iter_mut() {
+        if refresh_all || visibility.is_added() {
+            let shown = match fog.visibility_at(*pos) {
+                TileVisibility::Visible => Visibility::Inherited,
+                _ => Visibility::Hidden,
+            };
+            visibility.set_if_neq(shown);
+        }
+    }
+}
+
+// Shows the part of the left panel for what is selected, and fills in the plant, tile and band inspectors
+#[allow(clippy::too_many_arguments)]
+pub fn update_inspector_panel_system(
+    panel_state: Res<LeftPanelState>,
+    mut section_query: Query<(&PanelSection, &mut Node)>,
+    mut line_query: Query<(&InspectorLine, &mut Text)>,
+    plant_query: Query<(&PlantMarker, &FoodSource, Has<Harvestable>, Option<&BeingConsumed>)>,
+    creature_query: Query<&Position, With<CreatureMarker>>,
+    game_grid: Res<GameGrid>,
+    band_center: Res<BandCenter>,
+    band_center_mode: Res<BandCenterMode>,
+    lineage_query: Query<&Lineage, With<CreatureMarker>>,
+    genealogy: Res<Genealogy>,
+) {
+    let section = panel_state.section();
+    for (panel_section, mut node) in section_query.iter_mut() {
+        let display = if Some(*panel_section) == section { Display::Flex } else { Display::None };
+        if node.display != display {
+            node.display = display;
+        }
+    }
+
+    for (line, mut text) in line_query.iter_mut() {
+        // Only the lines on show are kept up to date
+        if Some(line.section()) != section {
+            continue;
+        }
+        let value = match (&*panel_state, *line) {
+            (LeftPanelState::Plant(entity), line) => match plant_query.get(*entity) {
+                Ok((marker, food, harvestable, being_consumed)) => match line {
+                    InspectorLine::PlantType => format!("Type: {:?}", marker.plant_type),
+                    InspectorLine::PlantNutrition => format!("Nutrition: {}", food.nutrition_value),
+                    _ => match (being_consumed, harvestable) {
+                        (Some(consumed), _) => format!("Status: being eaten by {:?}", consumed.consumer_entity),
+                        (None, true) => "Status: ripe".to_string(),
+                        (None, false) => "Status: not ripe".to_string(),
+                    },
+                },
+                // The plant has been eaten or destroyed since it was selected
+                Err(_) => match line {
+                    InspectorLine::PlantStatus => "Status: gone".to_string(),
+                    _ => continue,
+                },
+            },
+            (LeftPanelState::Tile(pos), line) => {
+                let tile = &game_grid.tiles[pos.y as usize][pos.x as usize];
+                match line {
+                    InspectorLine::TileKind => format!("Kind: {:?} ({:?}) at ({}, {})", tile.kind, tile.surface, pos.x, pos.y),
+                    InspectorLine::TileMoveCost => match game_grid.step_cost(*pos, MovementMode::Walk) {
+                        Some(cost) => format!("Move cost: {}", cost),
+                        None => "Move cost: impassable".to_string(),
+                    },
+                    InspectorLine::TileFertility => format!("Fertility: {:.2}/{:.2}", tile.fertility, tile.kind.natural_fertility()),
+                    _ => format!("Elevation: {:.2}", tile.elevation),
+                }
+            }
+            (LeftPanelState::Band, InspectorLine::BandCenter) => format!("Center: ({}, {})", band_center.0.x, band_center.0.y),
+            (LeftPanelState::Band, InspectorLine::BandMembers) => {
+                let away = creature_query.iter().filter(|pos| is_outside_band_radius(**pos, band_center.0)).count();
+                format!("Members: {} ({} away)", creature_query.iter().count(), away)
+            }
+            (LeftPanelState::Band, InspectorLine::BandLineages) => {
+                // Share of the living population descended from each founder, largest first
+                let mut counts: HashMap<Entity, usize> = HashMap::new();
+                for lineage in lineage_query.iter() {
+                    *counts.entry(lineage.founder).or_default() += 1;
+                }
+                let total = counts.values().sum::<usize>().max(1);
+                // Ties go in founder order so the line doesn't shuffle from frame to frame
+                let mut counts: Vec<_> = counts.into_iter().collect();
+                counts.sort_by_key(|(founder, count)| (std::cmp::Reverse(*count), genealogy.id_of(*founder)));
+                let shares: Vec<String> = counts.iter()
+                    .map(|(founder, count)| format!("{} {}%", genealogy.name(*founder), count * 100 / total))
+                    .collect();
+                format!("Lineages: {}", if shares.is_empty() { "-".to_string() } else { shares.join(", ") })
+            }
+            (LeftPanelState::Band, _) => match *band_center_mode {
+                BandCenterMode::Auto => "Mode: auto".to_string(),
+                BandCenterMode::Manual(pos) => format!("Mode: manual, gathering at ({}, {})", pos.x, pos.y),
+                BandCenterMode::Migrating(pos) => format!("Mode: migrating to ({}, {})", pos.x, pos.y),
+            },
+            _ => continue,
+        };
+        if text.0 != value {
+            text.0 = value;
+        }
+    }
+}
+
+// Draws the rectangle of a box selection while the left button is held
+pub fn selection_box_system(
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    drag: Res<DragSelection>,
+    mut box_query: Query<(&mut Sprite, &mut Transform, &mut Visibility), With<SelectionBox>>,
+) {
+    let Ok((mut sprite, mut transform, mut visibility)) = box_query.single_mut() else {
+        return;
+    };
+    match (drag.0, cast_cursor_position(windows, cameras)) {
+        (Some(start), Some(end)) => {
+            sprite.custom_size = Some((end - start).abs());
+            transform.translation = ((start + end) / 2.0).extend(transform.translation.z);
+            visibility.set_if_neq(Visibility::Inherited);
+        }
+        _ => {
+            visibility.set_if_neq(Visibility::Hidden);
+        }
+    }
+}
+
+pub fn night_overlay_system(
+    time_of_day: Res<TimeOfDay>,
+    mut overlay_query: Query<&mut Sprite, With<NightOverlay>>,
+) {
+    if time_of_day.is_changed()
+        && let Ok(mut sprite) = overlay_query.single_mut()
+    {
+        sprite.color.set_alpha(time_of_day.darkness() * NIGHT_OVERLAY_ALPHA);
+    }
+}
+
 pub fn update_tick_text_system(
     tick_count: Res<TickCount>,
     mut query: Query<&mut Text, With<TickText>>,
 ) {
-    if tick_count.is_changed() {
-        if let Ok(mut text) = query.single_mut() {
-            text.clear();
-            text.push_str(&format!("Tick: {}", tick_count.0));
-        }
+    if tick_count.is_changed()
+        && let Ok(mut text) = query.single_mut()
+    {
+        text.clear();
+        text.push_str(&format!("Tick: {}", tick_count.0));
+    }
+}
+
+pub fn update_speed_text_system(
+    speed: Res<SimulationSpeed>,
+    state: Res<State<GameState>>,
+    mut query: Query<&mut Text, With<SpeedText>>,
+) {
+    if (speed.is_changed() || state.is_changed())
+        && let Ok(mut text) = query.single_mut()
+    {
+        let paused = if *state.get() == GameState::Paused { " (paused)" } else { "" };
+        text.clear();
+        text.push_str(&format!("Speed: {}{}", speed.label(), paused));
+    }
+}
+
+pub fn update_date_text_system(
+    calendar: Res<Calendar>,
+    weather: Res<Weather>,
+    mut query: Query<&mut Text, With<DateText>>,
+) {
+    if (calendar.is_changed() || weather.is_changed())
+        && let Ok(mut text) = query.single_mut()
+    {
+        let harsh = if weather.harsh_winter { ", harsh" } else { "" };
+        text.clear();
+        text.push_str(&format!(
+            "Day {}, {:?}, Year {} ({:?}{})",
+            calendar.day, calendar.season, calendar.year, weather.kind, harsh,
+        ));
     }
 }
 
@@ -213,53 +484,116 @@ pub fn update_population_text_system(
     population_count: Res<PopulationCount>,
     mut query: Query<&mut Text, With<PopulationText>>,
 ) {
-    if population_count.is_changed() {
-        if let Ok(mut text) = query.single_mut() {
-            text.clear();
-            text.push_str(&format!("Population: {}", population_count.0));
-        }
+    if population_count.is_changed()
+        && let Ok(mut text) = query.single_mut()
+    {
+        text.clear();
+        text.push_str(&format!("Population: {}", population_count.0));
     }
 }
 
+// The text lines of the selected-creature panel, each tagged with what it shows
+type SelectedPanelTexts = (
+    &'static mut Text,
+    Option<&'static SelectedEntityIdText>,
+    Option<&'static SelectedCaloriesText>,
+    Option<&'static SelectedHydrationText>,
+    Option<&'static SelectedRestText>,
+    Option<&'static SelectedWarmthText>,
+    Option<&'static SelectedPregnancyText>,
+    Option<&'static SelectedUtilityText>,
+);
+
+// Everything the selected-creature panel reads from a creature
+type SelectedCreatureData = (
+    Entity,
+    Option<&'static Calories>,
+    Option<&'static Hydration>,
+    Option<&'static Rest>,
+    Option<&'static BodyWarmth>,
+    Option<&'static Pregnant>,
+    Option<&'static ActionSleep>,
+    Option<&'static UtilityScores>,
+    Option<&'static PlayerOrder>,
+);
+
 pub fn update_selected_panel_system(
     panel_state: Res<LeftPanelState>,
     mut root_query: Query<&mut Node, With<SelectedPanelRoot>>,
-    mut text_nodes: Query<
-        (&mut Text, Option<&SelectedEntityIdText>, Option<&SelectedCaloriesText>, Option<&SelectedPregnancyText>),
-        Without<SelectedPanelRoot>
-    >,
-    creatures: Query<(Entity, Option<&Calories>, Option<&Pregnant>), With<CreatureMarker>>,
+    mut text_nodes: Query<SelectedPanelTexts, Without<SelectedPanelRoot>>,
+    creatures: Query<SelectedCreatureData, With<CreatureMarker>>,
 ) {
     if let Ok(mut node) = root_query.single_mut() {
-        node.display = match *panel_state {
-            LeftPanelState::Creature(_) => Display::Flex,
-            LeftPanelState::None => Display::None,
-        };
+        node.display = if panel_state.section().is_some() { Display::Flex } else { Display::None };
     }
 
     let mut entity_line: Option<String> = None;
     let mut calories_line: Option<String> = None;
+    let mut hydration_line: Option<String> = None;
+    let mut rest_line: Option<String> = None;
+    let mut warmth_line: Option<String> = None;
     let mut pregnancy_line: Option<String> = None;
+    let mut utility_line: Option<String> = None;
 
-    if let LeftPanelState::Creature(entity) = *panel_state {
-        if let Ok((cre_entity, calories_opt, pregnant_opt)) = creatures.get(entity) {
+    if let LeftPanelState::Group(entities) = &*panel_state {
+        // Several creatures selected: show totals and averages over those still alive
+        let members: Vec<_> = entities.iter().filter_map(|entity| creatures.get(*entity).ok()).collect();
+        let (mut calories, mut hydration, mut rest, mut warmth, mut pregnant, mut ordered) = (0, 0, 0, 0, 0, 0);
+        for (_, calories_opt, hydration_opt, rest_opt, warmth_opt, pregnant_opt, _, _, order_opt) in &members {
+            calories += calories_opt.map_or(0, |c| c.current);
+            hydration += hydration_opt.map_or(0, |h| h.current);
+            rest += rest_opt.map_or(0, |r| r.current);
+            warmth += warmth_opt.map_or(0, |w| w.current);
+            pregnant += pregnant_opt.is_some() as usize;
+            ordered += order_opt.is_some() as usize;
+        }
+        let count = members.len().max(1) as i32;
+        entity_line = Some(format!("Selected: {} creatures", members.len()));
+        calories_line = Some(format!("Avg calories: {}/{}", calories / count, HUMAN_MAX_CALORIES));
+        hydration_line = Some(format!("Avg hydration: {}/{}", hydration / count, HUMAN_MAX_HYDRATION));
+        rest_line = Some(format!("Avg rest: {}/{}", rest / count, HUMAN_MAX_REST));
+        warmth_line = Some(format!("Avg warmth: {}/{}", warmth / count, HUMAN_MAX_WARMTH));
+        pregnancy_line = Some(format!("Pregnant: {}", pregnant));
+        utility_line = Some(format!("Under orders: {}", ordered));
+    } else if let LeftPanelState::Creature(entity) = *panel_state {
+        if let Ok((cre_entity, calories_opt, hydration_opt, rest_opt, warmth_opt, pregnant_opt, sleeping_opt, scores_opt, order_opt)) = creatures.get(entity) {
             entity_line = Some(format!("Entity: {:?}", cre_entity));
             if let Some(cal) = calories_opt {
                 calories_line = Some(format!("Calories: {}/{}", cal.current, cal.max));
             }
+            if let Some(hyd) = hydration_opt {
+                hydration_line = Some(format!("Hydration: {}/{}", hyd.current, hyd.max));
+            }
+            if let Some(rest) = rest_opt {
+                let sleeping = if sleeping_opt.is_some() { " (asleep)" } else { "" };
+                rest_line = Some(format!("Rest: {}/{}{}", rest.current, rest.max, sleeping));
+            }
+            if let Some(warmth) = warmth_opt {
+                warmth_line = Some(format!("Warmth: {}/{}", warmth.current, warmth.max));
+            }
             pregnancy_line = Some(match pregnant_opt {
                 Some(p) => format!("Pregnancy: yes {}/{}", p.progress, p.max_progress),
                 None => "Pregnancy: no".to_string(),
             });
+            // A player order overrides the creature's own decision
+            if let Some(order) = order_opt {
+                utility_line = Some(format!("Order: {:?}", order));
+            } else if let Some(scores) = scores_opt {
+                utility_line = Some(format_utility_scores(scores));
+            }
         } else {
             // Selected entity no longer exists; hide panel
             entity_line = None;
             calories_line = None;
+            hydration_line = None;
+            rest_line = None;
+            warmth_line = None;
             pregnancy_line = None;
+            utility_line = None;
         }
     }
 
-    for (mut text, is_id, is_cal, is_preg) in text_nodes.iter_mut() {
+    for (mut text, is_id, is_cal, is_hyd, is_rest, is_warmth, is_preg, is_utility) in text_nodes.iter_mut() {
         if is_id.is_some() {
             let new_value = entity_line.as_deref().unwrap_or("Entity: -");
             // Avoid unnecessary text mutations
@@ -273,12 +607,307 @@ pub fn update_selected_panel_system(
                 text.clear();
                 text.push_str(new_value);
             }
+        } else if is_hyd.is_some() {
+            let new_value = hydration_line.as_deref().unwrap_or("Hydration: -/-");
+            if text.0 != new_value {
+                text.clear();
+                text.push_str(new_value);
+            }
+        } else if is_rest.is_some() {
+            let new_value = rest_line.as_deref().unwrap_or("Rest: -/-");
+            if text.0 != new_value {
+                text.clear();
+                text.push_str(new_value);
+            }
+        } else if is_warmth.is_some() {
+            let new_value = warmth_line.as_deref().unwrap_or("Warmth: -/-");
+            if text.0 != new_value {
+                text.clear();
+                text.push_str(new_value);
+            }
         } else if is_preg.is_some() {
             let new_value = pregnancy_line.as_deref().unwrap_or("Pregnancy: no");
             if text.0 != new_value {
                 text.clear();
                 text.push_str(new_value);
             }
+        } else if is_utility.is_some() {
+            let new_value = utility_line.as_deref().unwrap_or("Decision: -");
+            if text.0 != new_value {
+                text.clear();
+                text.push_str(new_value);
+            }
+        }
+    }
+}
+
+// Shows the charts panel while it is toggled on, and redraws the charts and their legends as history is recorded
+pub fn update_charts_system(
+    history: Res<History>,
+    chart_window: Res<ChartWindow>,
+    charts_visible: Res<ChartsVisible>,
+    mut images: ResMut<Assets<Image>>,
+    mut panel_query: Query<&mut Node, With<ChartsPanelRoot>>,
+    chart_query: Query<(&ChartKind, &ImageNode)>,
+    mut legend_query: Query<(&ChartLegend, &mut TextSpan)>,
+) {
+    if charts_visible.is_changed() {
+        for mut node in panel_query.iter_mut() {
+            node.display = if charts_visible.0 { Display::Flex } else { Display::None };
         }
     }
+    if !charts_visible.0 || !(history.is_changed() || chart_window.is_changed() || charts_visible.is_changed()) {
+        return;
+    }
+
+    let samples: Vec<&HistorySample> = history.latest(chart_window.ticks()).collect();
+    for (kind, image_node) in chart_query.iter() {
+        if let Some(image) = images.get_mut(&image_node.image) {
+            draw_chart(image, &samples, &chart_series(*kind));
+        }
+    }
+
+    // Events are totalled over the window; levels show their latest value
+    for (legend, mut span) in legend_query.iter_mut() {
+        let Some(series) = chart_series(legend.chart).into_iter().nth(legend.series) else {
+            continue;
+        };
+        let value: f32 = if series.per_tick {
+            samples.iter().map(|sample| (series.value)(sample)).sum()
+        } else {
+            samples.last().map_or(0.0, |sample| (series.value)(sample))
+        };
+        let new_value = format!("{}: {:.0}  ", series.label, value);
+        if span.0 != new_value {
+            span.0 = new_value;
+        }
+    }
+}
+
+// Family tree of the selected creature: its generation, line of descent back to its founder,
+// and its descendants indented by generation, with when and how the dead died
+pub fn update_family_tree_system(
+    panel_state: Res<LeftPanelState>,
+    genealogy: Res<Genealogy>,
+    mut text_query: Query<&mut Text, With<SelectedFamilyTreeText>>,
+) {
+    if !(panel_state.is_changed() || genealogy.is_changed()) {
+        return;
+    }
+    let Ok(mut text) = text_query.single_mut() else {
+        return;
+    };
+    let value = match *panel_state {
+        LeftPanelState::Creature(entity) => match genealogy.records.get(&entity) {
+            Some(record) => {
+                let mut lines = vec![format!(
+                    "Family: {}, generation {}, founder {}, born tick {}",
+                    genealogy.name(entity), record.generation, genealogy.name(record.founder), record.born_tick,
+                )];
+                let ancestors = genealogy.ancestors(entity);
+                if !ancestors.is_empty() {
+                    let names: Vec<String> = ancestors.iter().map(|ancestor| genealogy.name(*ancestor)).collect();
+                    lines.push(format!("Ancestry: {}", names.join(" < ")));
+                }
+                let descendants = genealogy.descendants(entity);
+                let alive = descendants.iter().filter(|(descendant, _)| genealogy.is_alive(*descendant)).count();
+                lines.push(format!("Children: {}, descendants: {} ({} alive)", record.children.len(), descendants.len(), alive));
+                for (descendant, depth) in descendants.iter().take(FAMILY_TREE_MAX_LINES) {
+                    let marker = match genealogy.records.get(descendant).and_then(|record| record.death) {
+                        Some((tick, cause)) => format!(" (died of {}, tick {})", cause.label().to_ascii_lowercase(), tick),
+                        None => String::new(),
+                    };
+                    lines.push(format!("{}{}{}", "  ".repeat(*depth as usize), genealogy.name(*descendant), marker));
+                }
+                if descendants.len() > FAMILY_TREE_MAX_LINES {
+                    lines.push(format!("  ... and {} more", descendants.len() - FAMILY_TREE_MAX_LINES));
+                }
+                lines.join("\n")
+            }
+            None => "Family: -".to_string(),
+        },
+        _ => "Family: -".to_string(),
+    };
+    if text.0 != value {
+        text.0 = value;
+    }
+}
+
+// Shows the event log panel while it is toggled on, newest event first
+pub fn update_event_log_panel_system(
+    event_log: Res<EventLog>,
+    genealogy: Res<Genealogy>,
+    event_log_visible: Res<EventLogVisible>,
+    mut panel_query: Query<&mut Node, With<EventLogPanelRoot>>,
+    mut text_query: Query<&mut Text, With<EventLogText>>,
+) {
+    if event_log_visible.is_changed() {
+        for mut node in panel_query.iter_mut() {
+            node.display = if event_log_visible.0 { Display::Flex } else { Display::None };
+        }
+    }
+    if !event_log_visible.0 || !(event_log.is_changed() || event_log_visible.is_changed()) {
+        return;
+    }
+    if let Ok(mut text) = text_query.single_mut() {
+        let lines: Vec<String> = event_log.entries.iter().rev()
+            .map(|entry| format!("Tick {}: {}", entry.tick, entry.event.describe(&genealogy)))
+            .collect();
+        text.0 = if lines.is_empty() { "No events yet".to_string() } else { lines.join("\n") };
+    }
+}
+
+// --- Helper Functions ---
+
+// One line on a chart
+pub struct ChartSeries {
+    pub label: &'static str,
+    pub color: Color,
+    pub value: fn(&HistorySample) -> f32,
+    pub per_tick: bool, // Counts events, so a pixel sums its ticks instead of showing the last one
+}
+
+pub fn chart_series(kind: ChartKind) -> Vec<ChartSeries> {
+    match kind {
+        ChartKind::Population => vec![
+            ChartSeries { label: "Creatures", color: Color::WHITE, value: |s| s.population as f32, per_tick: false },
+            ChartSeries { label: "Pregnant", color: Color::srgb(1.0, 0.5, 0.8), value: |s| s.pregnant as f32, per_tick: false },
+        ],
+        ChartKind::BirthsAndDeaths => vec![
+            ChartSeries { label: "Births", color: Color::srgb(0.3, 1.0, 0.3), value: |s| s.births as f32, per_tick: true },
+            ChartSeries { label: DeathCause::Starvation.label(), color: Color::srgb(1.0, 0.6, 0.1), value: |s| s.deaths[0] as f32, per_tick: true },
+            ChartSeries { label: DeathCause::Thirst.label(), color: Color::srgb(1.0, 1.0, 0.3), value: |s| s.deaths[1] as f32, per_tick: true },
+            ChartSeries { label: DeathCause::Cold.label(), color: Color::srgb(0.4, 0.7, 1.0), value: |s| s.deaths[2] as f32, per_tick: true },
+            ChartSeries { label: DeathCause::Drowning.label(), color: Color::srgb(0.6, 0.4, 1.0), value: |s| s.deaths[3] as f32, per_tick: true },
+        ],
+        ChartKind::Plants => vec![
+            ChartSeries { label: "Plants", color: Color::srgb(0.9, 0.8, 0.3), value: |s| s.plants as f32, per_tick: false },
+        ],
+        ChartKind::Calories => vec![
+            ChartSeries { label: "Calories", color: Color::srgb(1.0, 0.5, 0.3), value: |s| s.average_calories, per_tick: false },
+        ],
+    }
+}
+
+// Plots each series across the image, every column covering an equal share of the samples,
+// scaled so the highest point of any series reaches the top
+fn draw_chart(image: &mut Image, samples: &[&HistorySample], series: &[ChartSeries]) {
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let Some(data) = image.data.as_mut() else {
+        return;
+    };
+    let background = Color::srgb(0.08, 0.08, 0.10).to_srgba().to_u8_array();
+    for pixel in data.chunks_exact_mut(4) {
+        pixel.copy_from_slice(&background);
+    }
+    if samples.is_empty() {
+        return;
+    }
+
+    let columns: Vec<Vec<f32>> = series.iter().map(|series| {
+        (0..width).map(|x| {
+            let start = (x * samples.len() / width).min(samples.len() - 1);
+            let end = ((x + 1) * samples.len() / width).clamp(start + 1, samples.len());
+            let bucket = &samples[start..end];
+            if series.per_tick {
+                bucket.iter().map(|sample| (series.value)(sample)).sum()
+            } else {
+                (series.value)(bucket[bucket.len() - 1])
+            }
+        }).collect()
+    }).collect();
+    let max = columns.iter().flatten().fold(1.0_f32, |max, &value| max.max(value));
+
+    for (series, values) in series.iter().zip(&columns) {
+        let color = series.color.to_srgba().to_u8_array();
+        let mut previous_y = None;
+        for (x, &value) in values.iter().enumerate() {
+            let y = height - 1 - ((value / max) * (height - 1) as f32).round() as usize;
+            // Join each point to the last with a vertical run so steep changes stay connected
+            let (top, bottom) = match previous_y {
+                Some(previous_y) => (y.min(previous_y), y.max(previous_y)),
+                None => (y, y),
+            };
+            for row in top..=bottom {
+                let index = (row * width + x) * 4;
+                data[index..index + 4].copy_from_slice(&color);
+            }
+            previous_y = Some(y);
+        }
+    }
+}
+
+// Terrain color, with exhausted soil, worn trails and roads drawn over land
+pub fn tile_color(tile: &Tile, x: usize, y: usize) -> Color {
+    let mut base = terrain_color(tile.kind, x, y);
+    if tile.kind.is_water() {
+        return base;
+    }
+    // Soil worked below its natural fertility fades toward bare, dusty earth
+    let natural_level = (tile.kind.natural_fertility() * FERTILITY_LEVELS) as u8;
+    let exhaustion = natural_level.saturating_sub(tile.soil_level()) as f32 / natural_level.max(1) as f32;
+    if exhaustion > 0.0 {
+        base = base.mix(&Color::srgb(0.72, 0.64, 0.5), exhaustion * 0.7);
+    }
+    match tile.surface {
+        Surface::Natural => base,
+        Surface::Trail => base.mix(&Color::srgb(0.6, 0.5, 0.35), 0.4),
+        Surface::Road => base.mix(&Color::srgb(0.7, 0.62, 0.45), 0.75),
+    }
+}
+
+fn terrain_color(kind: TileKind, x: usize, y: usize) -> Color {
+    match kind {
+        TileKind::Empty => {
+            if (x + y).is_multiple_of(2) {
+                Color::srgb(0.4, 0.4, 0.4)
+            } else {
+                Color::srgb(0.5, 0.5, 0.5)
+            }
+        }
+        TileKind::Dirt => Color::srgb(0.5, 0.5, 0.5),
+        TileKind::Water => Color::srgb(0.0, 0.0, 1.0),
+        TileKind::Shallows => Color::srgb(0.15, 0.3, 1.0),
+        TileKind::Desert => Color::srgb(0.85, 0.75, 0.45),
+        TileKind::Mountain => Color::srgb(0.35, 0.3, 0.25),
+        TileKind::Lake => Color::srgb(0.1, 0.35, 0.9),
+        TileKind::River => Color::srgb(0.2, 0.55, 1.0),
+        TileKind::Floodplain => Color::srgb(0.4, 0.47, 0.3),
+    }
+}
+
+// Water darkens with depth; land runs from green lowlands through brown highlands
+// to white peaks, with a darker contour line every CONTOUR_INTERVAL of elevation
+fn elevation_color(tile: &Tile) -> Color {
+    if tile.kind.is_water() {
+        let depth = ((WATER_LEVEL - tile.elevation) / WATER_LEVEL).clamp(0.0, 1.0);
+        return Color::srgb(0.1 * (1.0 - depth), 0.4 * (1.0 - depth) + 0.1, 0.9 - 0.4 * depth);
+    }
+    let height = ((tile.elevation - WATER_LEVEL) / (1.0 - WATER_LEVEL)).clamp(0.0, 1.0);
+    let (low, high, t) = if height < 0.5 {
+        ((0.25, 0.5, 0.2), (0.55, 0.4, 0.25), height * 2.0)
+    } else {
+        ((0.55, 0.4, 0.25), (0.95, 0.95, 0.95), height * 2.0 - 1.0)
+    };
+    let mut shade = (
+        low.0 + (high.0 - low.0) * t,
+        low.1 + (high.1 - low.1) * t,
+        low.2 + (high.2 - low.2) * t,
+    );
+    if (tile.elevation / CONTOUR_INTERVAL).fract() < CONTOUR_LINE_WIDTH {
+        shade = (shade.0 * 0.6, shade.1 * 0.6, shade.2 * 0.6);
+    }
+    Color::srgb(shade.0, shade.1, shade.2)
+}
+
+// Lists every intent's score, highest first, marking the winner
+fn format_utility_scores(scores: &UtilityScores) -> String {
+    let mut sorted = scores.0.clone();
+    sorted.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let mut lines = String::from("Decision:");
+    for (index, (intent, score)) in sorted.iter().enumerate() {
+        let marker = if index == 0 { ">" } else { " " };
+        lines.push_str(&format!("\n{} {:?}: {:.2}", marker, intent, score));
+    }
+    lines
 }
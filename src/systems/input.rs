@@ -7,95 +7,241 @@ use bevy::input::mouse::{
 
 use crate::constants::*;
 use crate::resources::{
-    game_grid::SpatialGrid,
+    game_grid::{SpatialGrid, GameGrid, TileKind, MovementMode},
     camera::{CameraZoom, CameraPosition},
-    ui_elements::{BandCenterVisualizationEnabled, LeftPanelState},
-    band_center::{BandCenter, BandCenterMode},
+    ui_elements::{BandCenterVisualizationEnabled, LeftPanelState, MapRenderMode, DragSelection, ControlGroups, ChartsVisible, ChartWindow, EventLogVisible},
+    band_center::{BandCenter, BandCenterMode, ManualBandMoves},
+    fog_of_war::{FogOfWar, TileVisibility},
+    technology::{Technologies, Technology},
+    replay::{Replay, ReplayInput, ReplayOrder},
+    ui_elements::TickCount,
 };
 use crate::components::components::*;
 
+// Interaction of the UI panels that sit over the map, whose clicks and scrolling shouldn't reach it
+type PanelInteractions<'w, 's> = Query<'w, 's, &'static Interaction, Or<(With<ChartsPanelRoot>, With<ChartWindowButton>, With<EventLogPanelRoot>, With<ReplayTimeline>)>>;
 
+
+// Remembers where a left-button drag starts, in case it turns into a box selection
+pub fn drag_selection_start_system(
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut drag: ResMut<DragSelection>,
+    panel_query: PanelInteractions,
+) {
+    drag.0 = if pointer_over_panels(&panel_query) { None } else { cast_cursor_position(windows, cameras) };
+}
+
+// Releasing the left button either finishes a box selection or clicks a tile. Clicking a creature
+// selects it, and shift adds it to the selection or takes it back out; a drag selects every creature
+// in the box. Clicking a plant inspects it, alt-clicking inspects the tile, and clicking empty
+// ground moves the band center and shows the band
+#[allow(clippy::too_many_arguments)]
 pub fn cursor_click_system(
-    mut commands: Commands,
     windows: Query<&Window>,
     cameras: Query<(&Camera, &GlobalTransform)>,
     mouse_input: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    camera_zoom: Res<CameraZoom>,
+    mut drag: ResMut<DragSelection>,
     creature_query: Query<(Entity, &Position, &Calories), With<CreatureMarker>>,
-    plant_query: Query<(&Position, &FoodSource, &PlantMarker)>,
+    plant_query: Query<(), With<PlantMarker>>,
     grid: Res<SpatialGrid>,
-    mut band_center: ResMut<BandCenter>,
-    mut band_center_mode: ResMut<BandCenterMode>,
+    fog: Res<FogOfWar>,
+    (mut band_center, mut band_center_mode, mut band_moves): (ResMut<BandCenter>, ResMut<BandCenterMode>, ResMut<ManualBandMoves>),
     mut panel_state: ResMut<LeftPanelState>,
-    // Ensure only one creature has path visualization at a time
-    creatures_with_viz: Query<Entity, (With<CreatureMarker>, With<PathVisualizationEnabled>)>,
+    panel_query: PanelInteractions,
+    (mut replay, tick_count): (ResMut<Replay>, Res<TickCount>),
 ) {
-    // Only handle left mouse button clicks
-    if !mouse_input.just_pressed(MouseButton::Left) {
+    // Only handle left mouse button releases
+    if !mouse_input.just_released(MouseButton::Left) {
+        return;
+    }
+    let drag_start = drag.0.take();
+    if drag_start.is_none() && pointer_over_panels(&panel_query) {
         return;
     }
+    let Some(world_position) = cast_cursor_position(windows, cameras) else {
+        return;
+    };
+    let additive = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let mut selection = if additive { panel_state.selected_creatures().to_vec() } else { Vec::new() };
 
-    if let Some(world_position) = cast_cursor_position(windows, cameras) {
-        let tile_x = (world_position.x / TILE_SIZE).floor() + GRID_WIDTH as f32 / 2.0;
-        let tile_y = (world_position.y / TILE_SIZE).floor() + GRID_HEIGHT as f32 / 2.0;
+    if let Some(start) = drag_start
+        && start.distance(world_position) >= DRAG_SELECT_THRESHOLD * camera_zoom.0
+    {
+        let (corner_a, corner_b) = (world_to_grid(start), world_to_grid(world_position));
+        let (min_x, max_x) = (corner_a.x.min(corner_b.x), corner_a.x.max(corner_b.x));
+        let (min_y, max_y) = (corner_a.y.min(corner_b.y), corner_a.y.max(corner_b.y));
+        for (entity, pos, _) in creature_query.iter() {
+            if (min_x..=max_x).contains(&pos.x) && (min_y..=max_y).contains(&pos.y) && !selection.contains(&entity) {
+                selection.push(entity);
+            }
+        }
+        info!("Box selected {} creatures", selection.len());
+        *panel_state = LeftPanelState::from_creatures(selection);
+        return;
+    }
 
-        let position = Position { x: tile_x as i32, y: tile_y as i32 };
+    // Check if we're clicking on valid grid coordinates
+    let Some(position) = world_to_tile(world_position) else {
+        return;
+    };
 
-        // Check if we're clicking on valid grid coordinates
-        if position.x >= 0 && position.x < GRID_WIDTH as i32 && position.y >= 0 && position.y < GRID_HEIGHT as i32 {
-            let mut clicked_creature = false;
-            
-            if let Some(entities) = grid.0.get(&position) {
-                for entity in entities.iter() {
-                    // Handle creature clicks - toggle path visualization
-                    if let Ok((creature_entity, position, calories)) = creature_query.get(*entity) {
-                        clicked_creature = true;
-                        info!("Clicked creature - Entity: {:?}, Position: {:?}, Calories: {:?}", creature_entity, position, calories);
-
-                        // Select creature and ensure exclusive path visualization
-                        // Remove viz from any previously visualized creatures
-                        for other in creatures_with_viz.iter() {
-                            if other != creature_entity {
-                                commands.entity(other).remove::<PathVisualizationEnabled>();
-                            }
-                        }
-
-                        *panel_state = LeftPanelState::Creature(creature_entity);
-                        commands.entity(creature_entity).insert(PathVisualizationEnabled);
-                        info!("Selected creature {:?}", creature_entity);
-                    }
-                    
-                    // Still log plant info for debugging
-                    if let Ok((position, food_source, plant_marker)) = plant_query.get(*entity) {
-                        info!("Clicked plant - Entity: {:?}, Position: {:?}, Nutrition: {:?}, PlantType: {:?}", entity, position, food_source.nutrition_value, plant_marker.plant_type);
+    // Alt-click inspects the tile itself
+    if keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]) {
+        *panel_state = LeftPanelState::Tile(position);
+        return;
+    }
+
+    let mut clicked_creature = false;
+    let mut clicked_plant = None;
+    if let Some(entities) = grid.0.get(&position) {
+        for entity in entities.iter() {
+            if let Ok((creature_entity, position, calories)) = creature_query.get(*entity) {
+                clicked_creature = true;
+                info!("Clicked creature - Entity: {:?}, Position: {:?}, Calories: {:?}", creature_entity, position, calories);
+                match selection.iter().position(|selected| *selected == creature_entity) {
+                    Some(index) => {
+                        selection.remove(index);
                     }
+                    None => selection.push(creature_entity),
                 }
             }
-            
-            // If we didn't click on a creature, set band center to manual mode at this position
-            if !clicked_creature {
-                *panel_state = LeftPanelState::None;
-                *band_center_mode = BandCenterMode::Manual(position);
-                band_center.0 = position;
-                info!("Set band center to manual mode at position: {:?}", position);
+
+            // Plants hidden in the fog can't be picked out
+            if plant_query.contains(*entity) && fog.visibility_at(position) == TileVisibility::Visible {
+                clicked_plant = Some(*entity);
             }
         }
     }
+
+    if clicked_creature {
+        *panel_state = LeftPanelState::from_creatures(selection);
+    } else if let Some(plant) = clicked_plant.filter(|_| !additive) {
+        *panel_state = LeftPanelState::Plant(plant);
+    } else if !additive {
+        // If we didn't click on a creature or plant, set band center to manual mode at this position
+        *panel_state = LeftPanelState::Band;
+        // A replay plays back the band moves of its own run
+        if replay.is_playing() {
+            return;
+        }
+        *band_center_mode = BandCenterMode::Manual(position);
+        band_moves.0.push((band_center.0, position));
+        band_center.0 = position;
+        replay.record(tick_count.0, ReplayInput::BandCenter(position));
+        info!("Set band center to manual mode at position: {:?}", position);
+    }
+}
+
+// Right-clicking orders the selected creatures: onto a plant to eat it, out over water to build
+// a raft at the nearest shore once rafts are known, and anywhere else to walk there
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn order_click_system(
+    mut commands: Commands,
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    panel_state: Res<LeftPanelState>,
+    grid: Res<SpatialGrid>,
+    game_grid: Res<GameGrid>,
+    fog: Res<FogOfWar>,
+    technologies: Res<Technologies>,
+    creature_query: Query<(&CreatureId, Option<&ActionEat>, Has<Raft>), With<CreatureMarker>>,
+    plant_query: Query<(), EdiblePlant>,
+    being_consumed_query: Query<&BeingConsumed>,
+    panel_query: PanelInteractions,
+    mut replay: ResMut<Replay>,
+    tick_count: Res<TickCount>,
+) {
+    // A replay plays back the orders of its own run
+    if replay.is_playing() || pointer_over_panels(&panel_query) {
+        return;
+    }
+    let Some(position) = cast_cursor_position(windows, cameras).and_then(world_to_tile) else {
+        return;
+    };
+
+    // Plants hidden in the fog can't be picked out
+    let plant = grid.0.get(&position)
+        .and_then(|entities| entities.iter().find(|entity| plant_query.get(**entity).is_ok()))
+        .filter(|_| fog.visibility_at(position) == TileVisibility::Visible);
+    let kind = game_grid.tiles[position.y as usize][position.x as usize].kind;
+    let open_water = kind.is_water() && kind != TileKind::River && technologies.has(Technology::Rafts);
+
+    for &entity in panel_state.selected_creatures() {
+        let Ok((id, action_eat, has_raft)) = creature_query.get(entity) else {
+            continue;
+        };
+        let shore = (open_water && !has_raft)
+            .then(|| game_grid.nearest_enterable(position, MovementMode::Walk))
+            .flatten();
+        let (order, replay_order) = match (plant, shore) {
+            (Some(&plant), _) => (PlayerOrder::Eat(plant), ReplayOrder::Eat(position)),
+            (None, Some(shore)) => (PlayerOrder::BuildRaft(shore), ReplayOrder::BuildRaft(shore)),
+            (None, None) => (PlayerOrder::MoveTo(position), ReplayOrder::MoveTo(position)),
+        };
+        give_order(&mut commands, entity, order, action_eat, &being_consumed_query);
+        replay.record(tick_count.0, ReplayInput::Order { creature: id.0, order: replay_order });
+        info!("Ordered {:?} to {:?}", entity, order);
+    }
+}
+
+// H sends the selected creatures back to the band center
+pub fn return_home_order_system(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    panel_state: Res<LeftPanelState>,
+    creature_query: Query<(&CreatureId, Option<&ActionEat>), With<CreatureMarker>>,
+    being_consumed_query: Query<&BeingConsumed>,
+    mut replay: ResMut<Replay>,
+    tick_count: Res<TickCount>,
+) {
+    if !keys.just_pressed(KeyCode::KeyH) || replay.is_playing() {
+        return;
+    }
+    for &entity in panel_state.selected_creatures() {
+        if let Ok((id, action_eat)) = creature_query.get(entity) {
+            give_order(&mut commands, entity, PlayerOrder::ReturnHome, action_eat, &being_consumed_query);
+            replay.record(tick_count.0, ReplayInput::Order { creature: id.0, order: ReplayOrder::ReturnHome });
+            info!("Ordered {:?} to return home", entity);
+        }
+    }
+}
+
+// Ctrl+number saves the selection as a control group; the number alone selects the group again
+pub fn control_group_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut control_groups: ResMut<ControlGroups>,
+    mut panel_state: ResMut<LeftPanelState>,
+    creature_query: Query<(), With<CreatureMarker>>,
+) {
+    const DIGIT_KEYS: [KeyCode; 10] = [
+        KeyCode::Digit0, KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4,
+        KeyCode::Digit5, KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+    ];
+    let Some(index) = DIGIT_KEYS.iter().position(|key| keys.just_pressed(*key)) else {
+        return;
+    };
+
+    if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        control_groups.0[index] = panel_state.selected_creatures().to_vec();
+        info!("Saved {} creatures to control group {}", control_groups.0[index].len(), index);
+    } else {
+        // Creatures that have died since the group was saved drop out of it
+        control_groups.0[index].retain(|entity| creature_query.contains(*entity));
+        if !control_groups.0[index].is_empty() {
+            *panel_state = LeftPanelState::from_creatures(control_groups.0[index].clone());
+        }
+    }
 }
 
 pub fn clear_selection_on_escape_system(
     keyboard: Res<ButtonInput<KeyCode>>,
     mut panel_state: ResMut<LeftPanelState>,
-    mut creatures_with_viz: Query<(Entity, &mut PathVisualizationEnabled), With<CreatureMarker>>,
-    mut commands: Commands,
 ) {
-    if keyboard.just_pressed(KeyCode::Escape) {
-        if let LeftPanelState::Creature(entity) = *panel_state {
-            *panel_state = LeftPanelState::None;
-            // Remove visualization from the previously selected creature
-            if creatures_with_viz.get_mut(entity).is_ok() {
-                commands.entity(entity).remove::<PathVisualizationEnabled>();
-            }
-        }
+    if keyboard.just_pressed(KeyCode::Escape) && !matches!(*panel_state, LeftPanelState::None) {
+        *panel_state = LeftPanelState::None;
     }
 }
 
@@ -105,7 +251,12 @@ pub fn camera_zoom_system(
     mut camera_zoom: ResMut<CameraZoom>,
     camera_query: Query<Entity, With<Camera2d>>,
     windows: Query<&Window>,
+    panel_query: PanelInteractions,
 ) {
+    if pointer_over_panels(&panel_query) {
+        scroll_evr.clear();
+        return;
+    }
     for ev in scroll_evr.read() {
         let zoom_delta = match ev.unit {
             MouseScrollUnit::Line => ev.y * ZOOM_SPEED * camera_zoom.0,
@@ -118,9 +269,7 @@ pub fn camera_zoom_system(
             
             let scale_for_width = map_width / window.width();
             let scale_for_height = map_height / window.height();
-            let max_zoom_out = scale_for_width.max(scale_for_height);
-            
-            max_zoom_out
+            scale_for_width.max(scale_for_height)
         } else {
             5.0
         };
@@ -218,8 +367,122 @@ pub fn band_center_toggle_system(
     }
 }
 
+pub fn map_render_mode_toggle_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut render_mode: ResMut<MapRenderMode>,
+) {
+    if keys.just_pressed(KeyCode::KeyE) {
+        *render_mode = match *render_mode {
+            MapRenderMode::Terrain => MapRenderMode::Elevation,
+            MapRenderMode::Elevation => MapRenderMode::Terrain,
+        };
+        info!("Map render mode: {:?}", *render_mode);
+    }
+}
+
+pub fn fog_of_war_toggle_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut fog: ResMut<FogOfWar>,
+) {
+    if keys.just_pressed(KeyCode::KeyF) {
+        fog.enabled = !fog.enabled;
+        info!("Fog of war toggled: {}", fog.enabled);
+    }
+}
+
+pub fn charts_toggle_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut charts_visible: ResMut<ChartsVisible>,
+) {
+    if keys.just_pressed(KeyCode::KeyG) {
+        charts_visible.0 = !charts_visible.0;
+    }
+}
+
+// Picks the charts' time window from the buttons above them, highlighting the chosen one
+pub fn chart_window_button_system(
+    mut chart_window: ResMut<ChartWindow>,
+    interaction_query: Query<(&Interaction, &ChartWindowButton), Changed<Interaction>>,
+    mut button_query: Query<(&ChartWindowButton, &mut BackgroundColor)>,
+) {
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction == Interaction::Pressed {
+            *chart_window = button.0;
+        }
+    }
+    if chart_window.is_changed() {
+        for (button, mut background) in button_query.iter_mut() {
+            background.0 = if button.0 == *chart_window { CHART_BUTTON_SELECTED_COLOR } else { CHART_BUTTON_COLOR };
+        }
+    }
+}
+
+pub fn event_log_toggle_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut event_log_visible: ResMut<EventLogVisible>,
+) {
+    if keys.just_pressed(KeyCode::KeyL) {
+        event_log_visible.0 = !event_log_visible.0;
+    }
+}
+
+// Scrolls the event log with the mouse wheel while the pointer is over it
+pub fn event_log_scroll_system(
+    mut scroll_evr: EventReader<MouseWheel>,
+    mut panel_query: Query<(&Interaction, &mut ScrollPosition), With<EventLogPanelRoot>>,
+) {
+    for ev in scroll_evr.read() {
+        let delta = match ev.unit {
+            MouseScrollUnit::Line => ev.y * EVENT_LOG_SCROLL_SPEED,
+            MouseScrollUnit::Pixel => ev.y,
+        };
+        for (interaction, mut scroll) in panel_query.iter_mut() {
+            if *interaction != Interaction::None {
+                scroll.offset_y = (scroll.offset_y - delta).max(0.0);
+            }
+        }
+    }
+}
+
 // --- Helper Functions ---
 
+fn pointer_over_panels(panel_query: &PanelInteractions) -> bool {
+    panel_query.iter().any(|interaction| *interaction != Interaction::None)
+}
+
+// Grid coordinates under a world position, which may lie off the map
+pub fn world_to_grid(world_position: Vec2) -> Position {
+    let tile_x = (world_position.x / TILE_SIZE).floor() + GRID_WIDTH as f32 / 2.0;
+    let tile_y = (world_position.y / TILE_SIZE).floor() + GRID_HEIGHT as f32 / 2.0;
+    Position { x: tile_x as i32, y: tile_y as i32 }
+}
+
+// Grid tile under a world position, if it lies on the map
+pub fn world_to_tile(world_position: Vec2) -> Option<Position> {
+    let position = world_to_grid(world_position);
+    (position.x >= 0 && position.x < GRID_WIDTH as i32 && position.y >= 0 && position.y < GRID_HEIGHT as i32)
+        .then_some(position)
+}
+
+// Drops whatever the creature was doing, releasing any plant it was eating, and hands it the order
+pub fn give_order(
+    commands: &mut Commands,
+    entity: Entity,
+    order: PlayerOrder,
+    action_eat: Option<&ActionEat>,
+    being_consumed_query: &Query<&BeingConsumed>,
+) {
+    if let Some(action_eat) = action_eat
+        && being_consumed_query.get(action_eat.target_entity).is_ok_and(|consumed| consumed.consumer_entity == entity)
+    {
+        commands.entity(action_eat.target_entity).remove::<BeingConsumed>();
+    }
+    commands.entity(entity)
+        .remove::<(WantsToEat, WantsToDrink, WantsToSleep, WantsToWarm, WantsToIdle, WantsToProcreate, WantsToReturnToBand)>()
+        .remove::<(ActionTravelTo, ActionEat, ActionDrink, ActionSleep, ActionWarm, ActivePath, OutsideBandRadius)>()
+        .insert(order);
+}
+
 pub fn cast_cursor_position(
     windows: Query<&Window>,
     cameras: Query<(&Camera, &GlobalTransform)>,
@@ -4,3 +4,5 @@ pub mod graphics;
 pub mod gameplay; 
 pub mod creature;
 pub mod input;
+pub mod weather;
+pub mod replay;
\ No newline at end of file
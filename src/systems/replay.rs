@@ -0,0 +1,517 @@
+use bevy::ecs::change_detection::MAX_CHANGE_AGE;
+use bevy::ecs::component::{ComponentTicks, Tick};
+use bevy::ecs::entity::{EntityCloner, EntityHashMap, MapEntities};
+use bevy::ecs::entity_disabling::Disabled;
+use bevy::ecs::system::SystemChangeTick;
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use bevy::render::sync_world::RenderEntity;
+use bevy::ui::RelativeCursorPosition;
+use crate::components::components::*;
+use crate::constants::*;
+use crate::resources::{
+    band_center::{BandCenter, BandCenterMode, BandFlowField, BandFoodMemory, CreaturesInCamp, ManualBandMoves},
+    calendar::{PlantsFruiting, TimeOfDay},
+    event_log::EventLog,
+    fog_of_war::FogOfWar,
+    game_grid::{FootTraffic, SpatialGrid},
+    game_state::{GameState, SimulationSpeed},
+    genealogy::{Genealogy, NextCreatureId},
+    history::History,
+    nav_graph::PathSearchBudget,
+    replay::{Replay, ReplayMode, ReplayInput, ReplayOrder, ReplaySnapshots, SavedResource, WorldSnapshot},
+    seed::WorldSeed,
+    stats::StatsRecorder,
+    technology::Technologies,
+    ui_elements::{ControlGroups, LeftPanelState, MapRenderMode, PopulationCount, TickCount},
+    utility::UtilityConfig,
+    weather::Weather,
+    world_preset::WorldPreset,
+};
+use crate::systems::{
+    graphics::{insert_creature_visuals, insert_plant_visuals},
+    input::give_order,
+    setup::spawn_world,
+};
+
+// Starts writing the replay file once the world seed is known
+pub fn start_replay_recording_system(
+    mut replay: ResMut<Replay>,
+    world_seed: Res<WorldSeed>,
+    world_preset: Res<WorldPreset>,
+    utility_config: Res<UtilityConfig>,
+) {
+    if replay.mode != ReplayMode::Recording {
+        return;
+    }
+    match replay.start_recording(world_seed.0, *world_preset, &utility_config) {
+        Ok(()) => info!("Recording replay to {}", Replay::file_name(world_seed.0)),
+        Err(error) => warn!("Could not create replay file: {}", error),
+    }
+}
+
+// Applies the recorded inputs due before this tick, as the player made them in the recorded run
+#[allow(clippy::too_many_arguments)]
+pub fn replay_playback_system(
+    mut commands: Commands,
+    mut replay: ResMut<Replay>,
+    tick_count: Res<TickCount>,
+    (mut band_center, mut band_center_mode, mut band_moves): (ResMut<BandCenter>, ResMut<BandCenterMode>, ResMut<ManualBandMoves>),
+    mut next_state: ResMut<NextState<GameState>>,
+    creature_query: Query<(Entity, &CreatureId, Option<&ActionEat>), With<CreatureMarker>>,
+    plant_query: Query<(), With<PlantMarker>>,
+    being_consumed_query: Query<&BeingConsumed>,
+    grid: Res<SpatialGrid>,
+) {
+    if !replay.is_playing() {
+        return;
+    }
+    while let Some(&(tick, input)) = replay.inputs.front() {
+        if tick > tick_count.0 {
+            break;
+        }
+        replay.inputs.pop_front();
+        match input {
+            // Pauses are skipped while fast-forwarding
+            ReplayInput::Pause if replay.seek_target.is_none() => next_state.set(GameState::Paused),
+            ReplayInput::Pause | ReplayInput::Resume => {}
+            ReplayInput::BandCenter(position) => {
+                *band_center_mode = BandCenterMode::Manual(position);
+                band_moves.0.push((band_center.0, position));
+                band_center.0 = position;
+            }
+            ReplayInput::Order { creature, order } => {
+                let Some((entity, _, action_eat)) = creature_query.iter().find(|(_, id, _)| id.0 == creature) else {
+                    warn!("Replay order at tick {} for creature #{} that doesn't exist", tick, creature);
+                    continue;
+                };
+                let order = match order {
+                    ReplayOrder::MoveTo(position) => PlayerOrder::MoveTo(position),
+                    ReplayOrder::BuildRaft(position) => PlayerOrder::BuildRaft(position),
+                    ReplayOrder::ReturnHome => PlayerOrder::ReturnHome,
+                    ReplayOrder::Eat(position) => {
+                        let plant = grid.0.get(&position)
+                            .and_then(|entities| entities.iter().find(|entity| plant_query.contains(**entity)));
+                        match plant {
+                            Some(&plant) => PlayerOrder::Eat(plant),
+                            None => {
+                                warn!("Replay order at tick {} to eat a plant missing at {:?}", tick, position);
+                                continue;
+                            }
+                        }
+                    }
+                };
+                give_order(&mut commands, entity, order, action_eat, &being_consumed_query);
+            }
+        }
+    }
+}
+
+// Every REPLAY_KEYFRAME_INTERVAL ticks, records a checksum of the world, or during playback
+// compares it with the recorded one to catch a replay drifting from its run
+pub fn replay_keyframe_system(
+    mut replay: ResMut<Replay>,
+    tick_count: Res<TickCount>,
+    band_center: Res<BandCenter>,
+    creature_query: Query<(&CreatureId, &Position, &Calories), With<CreatureMarker>>,
+    plant_query: Query<&Position, With<PlantMarker>>,
+    ticks: SystemChangeTick,
+) {
+    if replay.is_playing() {
+        replay.sim_change_tick = ticks.this_run();
+    }
+    if replay.mode == ReplayMode::Off || !tick_count.0.is_multiple_of(REPLAY_KEYFRAME_INTERVAL) {
+        return;
+    }
+    // Wrapping sums don't depend on the order entities are visited in
+    let mut checksum = (band_center.0.x as u64) << 32 | band_center.0.y as u32 as u64;
+    for (id, pos, calories) in creature_query.iter() {
+        let value = (id.0 as u64) << 40 ^ (pos.x as u64) << 20 ^ pos.y as u64 ^ (calories.current as u64).rotate_left(48);
+        checksum = checksum.wrapping_add(value.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    }
+    for pos in plant_query.iter() {
+        checksum = checksum.wrapping_add(((pos.x as u64) << 20 ^ pos.y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F));
+    }
+
+    if replay.mode == ReplayMode::Recording {
+        replay.record_keyframe(tick_count.0, checksum);
+        return;
+    }
+    while let Some(&(tick, expected)) = replay.keyframes.front() {
+        if tick > tick_count.0 {
+            break;
+        }
+        replay.keyframes.pop_front();
+        if tick == tick_count.0 && expected != checksum && !replay.desynced {
+            replay.desynced = true;
+            warn!("Replay diverged from the recorded run at tick {}", tick);
+        }
+    }
+}
+
+// During playback, keeps a snapshot of the world every ReplaySnapshots interval, for later seeks to
+// resume from
+pub fn replay_snapshot_system(world: &mut World) {
+    let tile_events: Vec<Position> = world.resource_scope(|world, mut snapshots: Mut<ReplaySnapshots>| {
+        let events = world.resource::<Events<TileChanged>>();
+        snapshots.tile_cursor.read(events).map(|event| event.position).collect()
+    });
+    let tick = world.resource::<TickCount>().0;
+    if !world.resource::<Replay>().is_playing() || !world.resource::<ReplaySnapshots>().is_due(tick) {
+        return;
+    }
+
+    // Copies are spawned up front so components naming another creature or plant name its copy
+    let mut simulated = world.query_filtered::<Entity, Or<(With<CreatureMarker>, With<PlantMarker>)>>();
+    let sources: Vec<Entity> = simulated.iter(world).collect();
+    let mut copies = EntityHashMap::default();
+    for &source in &sources {
+        copies.insert(source, world.spawn(Disabled).id());
+    }
+    let disabled = world.register_component::<Disabled>();
+    let mut builder = EntityCloner::build(world);
+    builder.deny::<(Sprite, Aabb, Children, RenderEntity)>();
+    let mut cloner = builder.finish();
+    let mut entities = Vec::with_capacity(sources.len());
+    for source in sources {
+        let copy = cloner.clone_entity_mapped(world, source, &mut copies);
+        let source_ref = world.entity(source);
+        let ticks = world.entity(copy).archetype().components()
+            .filter(|&id| id != disabled)
+            .filter_map(|id| Some((id, source_ref.get_change_ticks_by_id(id)?)))
+            .collect();
+        entities.push((copy, ticks));
+    }
+
+    let replay = world.resource::<Replay>();
+    let mut in_camp = save_resource::<CreaturesInCamp>(world);
+    in_camp.value.map_entities(&mut copies);
+    let mut genealogy = save_resource::<Genealogy>(world);
+    genealogy.value.map_entities(&mut copies);
+    let mut event_log = world.resource::<EventLog>().entries.clone();
+    for entry in event_log.iter_mut() {
+        entry.event.map_entities(&mut copies);
+    }
+    let snapshot = WorldSnapshot {
+        tick,
+        change_tick: replay.sim_change_tick,
+        entities,
+        tile_events,
+        inputs: replay.inputs.clone(),
+        keyframes: replay.keyframes.clone(),
+        desynced: replay.desynced,
+        game_grid: save_resource(world),
+        nav_graph: save_resource(world),
+        tick_count: save_resource(world),
+        calendar: save_resource(world),
+        time_of_day: save_resource(world),
+        plants_fruiting: save_resource(world),
+        population: save_resource(world),
+        band_center: save_resource(world),
+        band_center_mode: save_resource(world),
+        band_flow_field: save_resource(world),
+        band_food_memory: save_resource(world),
+        in_camp,
+        manual_band_moves: save_resource(world),
+        foot_traffic: save_resource(world),
+        search_budget: save_resource(world),
+        weather: save_resource(world),
+        technologies: save_resource(world),
+        sim_rng: save_resource(world),
+        next_creature_id: save_resource(world),
+        genealogy,
+        history: world.resource::<History>().clone(),
+        event_log,
+        fog: world.resource::<FogOfWar>().clone(),
+    };
+    let dropped = world.resource_mut::<ReplaySnapshots>().insert(snapshot);
+    for copy in dropped {
+        world.despawn(copy);
+    }
+}
+
+// During playback, [ and ] seek a season back or forward, and clicking the timeline seeks to that
+// point of the recording. A seek re-simulates at full speed, from the nearest snapshot before its
+// target, or from the first tick if it goes back past them all
+#[allow(clippy::type_complexity)]
+pub fn replay_seek_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut replay: ResMut<Replay>,
+    tick_count: Res<TickCount>,
+    timeline_query: Query<(&Interaction, &RelativeCursorPosition), (With<ReplayTimeline>, Changed<Interaction>)>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+) {
+    if !replay.is_playing() {
+        return;
+    }
+    let mut target = replay.pending_seek.take();
+    if keys.just_pressed(KeyCode::BracketRight) {
+        target = Some(tick_count.0 + REPLAY_SEEK_TICKS);
+    }
+    if keys.just_pressed(KeyCode::BracketLeft) {
+        target = Some(tick_count.0.saturating_sub(REPLAY_SEEK_TICKS));
+    }
+    if let Ok((interaction, cursor)) = timeline_query.single()
+        && *interaction == Interaction::Pressed
+        && let Some(position) = cursor.normalized
+    {
+        target = Some((position.x.clamp(0.0, 1.0) * replay.end_tick as f32).round() as u32);
+    }
+
+    let Some(target) = target else {
+        return;
+    };
+    replay.seek(target, tick_count.0);
+    if replay.seek_target.is_some() {
+        fixed_time.set_timestep_hz(MAX_TICK_RATE_HZ);
+        next_state.set(GameState::Running);
+        info!("Seeking to tick {}", target);
+    }
+}
+
+// Takes the world to where a seek resumes from: the latest snapshot at or before the target, when
+// that saves simulating ticks, or else the first tick for a seek back in time. The seek then
+// re-simulates the rest of the way
+pub fn replay_rewind_system(world: &mut World) {
+    let replay = world.resource::<Replay>();
+    let rewind_requested = replay.rewind_requested;
+    let current_tick = world.resource::<TickCount>().0;
+    let snapshot_tick = replay.seek_target
+        .and_then(|target| world.resource::<ReplaySnapshots>().latest_at(target))
+        .map(|snapshot| snapshot.tick)
+        .filter(|&tick| rewind_requested || tick > current_tick);
+    if let Some(tick) = snapshot_tick {
+        restore_snapshot(world, tick);
+        info!("Replay resumed from the snapshot at tick {}", tick);
+    } else if rewind_requested {
+        restart_from_seed(world);
+        info!("Replay restarted from the first tick");
+    } else {
+        return;
+    }
+
+    // A seek to the tick the world now stands on is already there
+    let tick = world.resource::<TickCount>().0;
+    let mut replay = world.resource_mut::<Replay>();
+    if replay.seek_target == Some(tick) {
+        replay.seek_target = None;
+        let tick_rate = world.resource::<SimulationSpeed>().tick_rate_hz();
+        world.resource_mut::<Time<Fixed>>().set_timestep_hz(tick_rate);
+        world.resource_mut::<NextState<GameState>>().set(GameState::Paused);
+    }
+}
+
+// Clears everything the simulation has built up and spawns the world again from its seed
+fn restart_from_seed(world: &mut World) {
+    despawn_simulated(world);
+
+    world.insert_resource(Weather::default());
+    world.insert_resource(TimeOfDay::default());
+    world.insert_resource(PlantsFruiting::default());
+    world.insert_resource(Technologies::default());
+    world.insert_resource(BandFlowField::default());
+    world.insert_resource(BandFoodMemory::default());
+    world.insert_resource(CreaturesInCamp::default());
+    world.insert_resource(ManualBandMoves::default());
+    world.insert_resource(FootTraffic::default());
+    world.insert_resource(PathSearchBudget::default());
+    world.insert_resource(History::default());
+    world.insert_resource(Genealogy::default());
+    world.insert_resource(StatsRecorder::default());
+    world.insert_resource(LeftPanelState::default());
+    world.insert_resource(ControlGroups::default());
+    let fog_enabled = world.resource::<FogOfWar>().enabled;
+    let mut fog = FogOfWar::default();
+    fog.enabled = fog_enabled;
+    world.insert_resource(fog);
+    let to_file = world.resource::<EventLog>().to_file;
+    world.insert_resource(EventLog { to_file, ..EventLog::default() });
+    clear_sim_events(world);
+
+    let world_seed = world.resource::<WorldSeed>().0;
+    let world_preset = *world.resource::<WorldPreset>();
+    let mut next_creature_id = NextCreatureId::default();
+    spawn_world(&mut world.commands(), world_preset, &mut next_creature_id, world_seed);
+    world.flush();
+    world.insert_resource(next_creature_id);
+    // The terrain is back to how it was generated
+    world.resource_mut::<MapRenderMode>().set_changed();
+
+    world.resource_mut::<Replay>().rewind();
+}
+
+// Puts the world back as it stood in the snapshot taken at a tick
+fn restore_snapshot(world: &mut World, tick: u32) {
+    despawn_simulated(world);
+    clear_sim_events(world);
+
+    world.resource_scope(|world, mut snapshots: Mut<ReplaySnapshots>| {
+        let Some(snapshot) = snapshots.latest_at(tick) else {
+            return;
+        };
+        // Change ticks move up by however far the world has got since the snapshot, so change
+        // detection sees the same ticks as having passed
+        let then = snapshot.change_tick;
+        let now = world.resource::<Replay>().sim_change_tick;
+        let shift = |ticks: ComponentTicks| (shift_tick(ticks.added, then, now), shift_tick(ticks.changed, then, now));
+
+        let mut restored = EntityHashMap::default();
+        for &(copy, _) in &snapshot.entities {
+            restored.insert(copy, world.spawn_empty().id());
+        }
+        let mut builder = EntityCloner::build(world);
+        builder.deny::<Disabled>();
+        let mut cloner = builder.finish();
+        for (copy, component_ticks) in &snapshot.entities {
+            let entity = cloner.clone_entity_mapped(world, *copy, &mut restored);
+            let mut entity_mut = world.entity_mut(entity);
+            for &(id, ticks) in component_ticks {
+                let (added, changed) = shift(ticks);
+                if let Ok(mut component) = entity_mut.get_mut_by_id(id) {
+                    component.set_last_added(added);
+                    component.set_last_changed(changed);
+                }
+            }
+        }
+        // Sprites go back on in the same order, so the entities end up in their tables' rows
+        let asset_server = world.resource::<AssetServer>().clone();
+        let mut rng = rand::rng();
+        for &(copy, _) in &snapshot.entities {
+            let entity = restored[&copy];
+            let entity_ref = world.entity(entity);
+            let Some(&pos) = entity_ref.get::<Position>() else {
+                continue;
+            };
+            let is_creature = entity_ref.contains::<CreatureMarker>();
+            let mut commands = world.commands();
+            if is_creature {
+                insert_creature_visuals(&mut commands, entity, pos, &asset_server, &mut rng);
+            } else {
+                insert_plant_visuals(&mut commands, entity, pos, &asset_server);
+            }
+            world.flush();
+        }
+
+        restore_resource(world, &snapshot.game_grid, then, now);
+        restore_resource(world, &snapshot.nav_graph, then, now);
+        restore_resource(world, &snapshot.tick_count, then, now);
+        restore_resource(world, &snapshot.calendar, then, now);
+        restore_resource(world, &snapshot.time_of_day, then, now);
+        restore_resource(world, &snapshot.plants_fruiting, then, now);
+        restore_resource(world, &snapshot.population, then, now);
+        restore_resource(world, &snapshot.band_center, then, now);
+        restore_resource(world, &snapshot.band_center_mode, then, now);
+        restore_resource(world, &snapshot.band_flow_field, then, now);
+        restore_resource(world, &snapshot.band_food_memory, then, now);
+        restore_resource(world, &snapshot.in_camp, then, now);
+        restore_resource(world, &snapshot.manual_band_moves, then, now);
+        restore_resource(world, &snapshot.foot_traffic, then, now);
+        restore_resource(world, &snapshot.search_budget, then, now);
+        restore_resource(world, &snapshot.weather, then, now);
+        restore_resource(world, &snapshot.technologies, then, now);
+        restore_resource(world, &snapshot.sim_rng, then, now);
+        restore_resource(world, &snapshot.next_creature_id, then, now);
+        restore_resource(world, &snapshot.genealogy, then, now);
+        world.resource_mut::<Genealogy>().map_entities(&mut restored);
+        world.resource_mut::<CreaturesInCamp>().map_entities(&mut restored);
+
+        // The tiles changed on the snapshot's tick are still to be read by the next one
+        let mut tile_events = world.resource_mut::<Events<TileChanged>>();
+        for &position in &snapshot.tile_events {
+            tile_events.send(TileChanged { position });
+        }
+        let tile_cursor = tile_events.get_cursor_current();
+
+        world.insert_resource(snapshot.history.clone());
+        let mut fog = snapshot.fog.clone();
+        fog.enabled = world.resource::<FogOfWar>().enabled;
+        world.insert_resource(fog);
+        let mut event_log = world.resource_mut::<EventLog>();
+        event_log.entries = snapshot.event_log.clone();
+        for entry in event_log.entries.iter_mut() {
+            entry.event.map_entities(&mut restored);
+        }
+        world.resource_mut::<Replay>().resume(snapshot);
+        snapshots.tile_cursor = tile_cursor;
+    });
+
+    world.insert_resource(StatsRecorder::default());
+    world.insert_resource(LeftPanelState::default());
+    world.insert_resource(ControlGroups::default());
+    // The map and the HUD redraw from the restored state
+    world.resource_mut::<MapRenderMode>().set_changed();
+    world.resource_mut::<TickCount>().set_changed();
+    world.resource_mut::<PopulationCount>().set_changed();
+    world.resource_mut::<Weather>().set_changed();
+    world.resource_mut::<TimeOfDay>().set_changed();
+}
+
+fn despawn_simulated(world: &mut World) {
+    let mut simulated = world.query_filtered::<Entity, Or<(With<CreatureMarker>, With<PlantMarker>)>>();
+    let entities: Vec<Entity> = simulated.iter(world).collect();
+    for entity in entities {
+        world.despawn(entity);
+    }
+}
+
+fn clear_sim_events(world: &mut World) {
+    world.resource_mut::<Events<SimEvent>>().clear();
+    world.resource_mut::<Events<FoodTargetInvalidated>>().clear();
+    world.resource_mut::<Events<TileChanged>>().clear();
+    world.resource_mut::<Events<TileCostChanged>>().clear();
+    world.resource_mut::<Events<DisasterOccurred>>().clear();
+}
+
+// A tick as far behind `now` as it was behind `then`, kept within the age change detection allows
+fn shift_tick(tick: Tick, then: Tick, now: Tick) -> Tick {
+    let age = then.get().wrapping_sub(tick.get()).min(MAX_CHANGE_AGE);
+    Tick::new(now.get().wrapping_sub(age))
+}
+
+fn save_resource<R: Resource + Clone>(world: &World) -> SavedResource<R> {
+    let resource = world.resource_ref::<R>();
+    SavedResource {
+        value: resource.clone(),
+        ticks: ComponentTicks { added: resource.added(), changed: resource.last_changed() },
+    }
+}
+
+fn restore_resource<R: Resource + Clone>(world: &mut World, saved: &SavedResource<R>, then: Tick, now: Tick) {
+    world.insert_resource(saved.value.clone());
+    let mut resource = world.resource_mut::<R>();
+    resource.set_last_added(shift_tick(saved.ticks.added, then, now));
+    resource.set_last_changed(shift_tick(saved.ticks.changed, then, now));
+}
+
+// Stops a seek on its target tick, dropping the time banked for further ticks this frame
+pub fn finish_replay_seek_system(
+    mut replay: ResMut<Replay>,
+    tick_count: Res<TickCount>,
+    speed: Res<SimulationSpeed>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+) {
+    if replay.seek_target.is_some_and(|target| tick_count.0 >= target) {
+        replay.seek_target = None;
+        fixed_time.set_timestep_hz(speed.tick_rate_hz());
+        let overstep = fixed_time.overstep();
+        fixed_time.discard_overstep(overstep);
+        next_state.set(GameState::Paused);
+    }
+}
+
+// Fills the timeline up to the current tick
+pub fn update_replay_timeline_system(
+    replay: Res<Replay>,
+    tick_count: Res<TickCount>,
+    mut fill_query: Query<&mut Node, With<ReplayTimelineFill>>,
+) {
+    if !replay.is_playing() || !tick_count.is_changed() {
+        return;
+    }
+    if let Ok(mut node) = fill_query.single_mut() {
+        let progress = tick_count.0 as f32 / replay.end_tick.max(1) as f32;
+        node.width = Val::Percent(progress.min(1.0) * 100.0);
+    }
+}
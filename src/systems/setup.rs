@@ -1,7 +1,10 @@
 use bevy::prelude::*;
-use rand::Rng;
+use bevy::ui::RelativeCursorPosition;
+use rand::{Rng, SeedableRng};
 use rand_pcg::Pcg32;
 use noise::{NoiseFn, Perlin};
+use bevy::asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
 
 use crate::constants::*;
 use crate::resources::{
@@ -9,57 +12,92 @@ use crate::resources::{
         GameGrid,
         TileKind,
         Tile,
+        Surface,
         SpatialGrid,
     },
     band_center::{BandCenter, BandCenterMode},
-    ui_elements::{TickCount, PopulationCount},
-    seed::WorldSeed,
+    ui_elements::{TickCount, PopulationCount, ChartWindow},
+    calendar::Calendar,
+    seed::{WorldSeed, SimRng},
     camera::CameraZoom,
+    world_preset::{WorldPreset, Continent, Barrier, BarrierKind},
+    nav_graph::NavGraph,
+    genealogy::NextCreatureId,
+    replay::Replay,
 };
 use crate::components::components::*;
+use crate::systems::graphics::{tile_color, chart_series};
 
-pub fn setup_system(mut commands: Commands, camera_zoom: Res<CameraZoom>) {
+pub fn setup_system(
+    mut commands: Commands,
+    camera_zoom: Res<CameraZoom>,
+    world_preset: Res<WorldPreset>,
+    mut next_creature_id: ResMut<NextCreatureId>,
+    replay: Res<Replay>,
+) {
     commands.spawn((
-        Camera2d::default(),
+        Camera2d,
         Projection::from(OrthographicProjection {
             scale: camera_zoom.0,
             ..OrthographicProjection::default_2d()
         }),
     ));
 
+    // A replay re-simulates the run it was recorded from
+    let world_seed = replay.seed.unwrap_or_else(generate_seed);
+    spawn_world(&mut commands, *world_preset, &mut next_creature_id, world_seed);
+}
+
+// Generates the world from its seed: the founders, the plants and the simulation's resources.
+// Also used to restart a replay from its first tick
+pub fn spawn_world(
+    commands: &mut Commands,
+    world_preset: WorldPreset,
+    next_creature_id: &mut NextCreatureId,
+    world_seed: u32,
+) {
     // --- Resource Setup ---
-    let world_seed = generate_seed();
-    let grid_tiles = generate_height_map(world_seed);
-    // Find dirt tiles near map center for creatures
-    let creature_positions = find_dirt_near_center(&grid_tiles);
+    let grid_tiles = generate_height_map(world_seed, world_preset);
+    // Find dirt tiles near the preset's spawn point for creatures
+    let creature_positions = find_dirt_near_spawn(&grid_tiles, world_preset.spawn_point());
 
     // --- Spawning Initial Entities ---
     // Spawn Creatures
-    commands.spawn((
-        CreatureMarker,
-        creature_positions.0,
-        Calories { current: HUMAN_MAX_CALORIES, max: HUMAN_MAX_CALORIES },
-    ));
-    commands.spawn((
-        CreatureMarker,
-        creature_positions.1,
-        Calories { current: HUMAN_MAX_CALORIES, max: HUMAN_MAX_CALORIES },
-    ));
+    // Each founder starts a lineage of its own
+    for position in [creature_positions.0, creature_positions.1] {
+        let founder = commands.spawn((
+            CreatureMarker,
+            next_creature_id.take(),
+            position,
+            Calories { current: HUMAN_MAX_CALORIES, max: HUMAN_MAX_CALORIES },
+            Hydration { current: HUMAN_MAX_HYDRATION, max: HUMAN_MAX_HYDRATION },
+            Rest { current: HUMAN_MAX_REST, max: HUMAN_MAX_REST },
+            BodyWarmth { current: HUMAN_MAX_WARMTH, max: HUMAN_MAX_WARMTH },
+            FoodMemory::default(),
+        )).id();
+        commands.entity(founder).insert(Lineage::founder(founder));
+    }
 
     // Spawn Plants using noise-based wheat generation
-    generate_wheat_patches(&mut commands, &grid_tiles, world_seed);
+    generate_wheat_patches(commands, &grid_tiles, world_seed);
 
-    commands.insert_resource(GameGrid { tiles: grid_tiles });
+    let game_grid = GameGrid { tiles: grid_tiles };
+    commands.insert_resource(NavGraph::new(&game_grid));
+    commands.insert_resource(game_grid);
     commands.insert_resource(SpatialGrid::default());
     commands.insert_resource(TickCount::default());
+    commands.insert_resource(Calendar::default());
     commands.insert_resource(PopulationCount::default());
     commands.insert_resource(BandCenter(Position { x: 0, y: 0 }));
     commands.insert_resource(BandCenterMode::Auto);
     commands.insert_resource(WorldSeed(world_seed));
+    commands.insert_resource(SimRng(Pcg32::seed_from_u64(world_seed as u64)));
 }
 
 pub fn spawn_ui (
     mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    replay: Res<Replay>,
 ) {
     let root = commands
         .spawn((
@@ -89,7 +127,7 @@ pub fn spawn_ui (
         ))
         .id();
 
-    // Left side: current game tick and population
+    // Left side: current game tick, date and population
     commands.entity(top_bar).with_children(|bar| {
         bar.spawn((
             Text::new("Tick: 0"),
@@ -110,6 +148,25 @@ pub fn spawn_ui (
             TextColor(Color::WHITE),
             Name::new("Separator"),
         ));
+        bar.spawn((
+            Text::new("Day 1, Spring, Year 1"),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            DateText,
+            Name::new("DateText"),
+        ));
+        bar.spawn((
+            Text::new(" | "),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            Name::new("Separator"),
+        ));
         bar.spawn((
             Text::new("Population: 2"),
             TextFont {
@@ -120,6 +177,49 @@ pub fn spawn_ui (
             PopulationText,
             Name::new("PopulationText"),
         ));
+        bar.spawn((
+            Text::new(" | "),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            Name::new("Separator"),
+        ));
+        bar.spawn((
+            Text::new("Speed: 1x"),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            SpeedText,
+            Name::new("SpeedText"),
+        ));
+        // Replay timeline, only shown during playback
+        bar.spawn((
+            Node {
+                width: Val::Px(REPLAY_TIMELINE_WIDTH),
+                height: Val::Px(12.0),
+                margin: UiRect::left(Val::Px(16.0)),
+                display: if replay.is_playing() { Display::Flex } else { Display::None },
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.2, 0.2, 0.25)),
+            Interaction::default(),
+            RelativeCursorPosition::default(),
+            ReplayTimeline,
+            Name::new("Replay Timeline"),
+        )).with_child((
+            Node {
+                width: Val::Percent(0.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.4, 0.6, 0.9)),
+            ReplayTimelineFill,
+            Name::new("Replay Timeline Fill"),
+        ));
     });
 
     // --- Main Row (left panel + viewport spacer) -----------------------------
@@ -155,32 +255,125 @@ pub fn spawn_ui (
 
     commands.entity(left_panel).with_children(|panel| {
         panel.spawn((
-            Text::new("Selected Creature"),
-            TextFont { font_size: 18.0, ..default() },
-            TextColor(Color::WHITE),
-            Name::new("SelectedTitle"),
-        ));
-        panel.spawn((
-            Text::new("Entity: -"),
-            TextFont { font_size: 14.0, ..default() },
-            TextColor(Color::WHITE),
-            SelectedEntityIdText,
-            Name::new("SelectedEntityIdText"),
-        ));
-        panel.spawn((
-            Text::new("Calories: -/-"),
-            TextFont { font_size: 14.0, ..default() },
-            TextColor(Color::WHITE),
-            SelectedCaloriesText,
-            Name::new("SelectedCaloriesText"),
-        ));
-        panel.spawn((
-            Text::new("Pregnancy: no"),
-            TextFont { font_size: 14.0, ..default() },
-            TextColor(Color::WHITE),
-            SelectedPregnancyText,
-            Name::new("SelectedPregnancyText"),
-        ));
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            PanelSection::Creature,
+            Name::new("Creature Section"),
+        )).with_children(|panel| {
+            panel.spawn((
+                Text::new("Selected Creature"),
+                TextFont { font_size: 18.0, ..default() },
+                TextColor(Color::WHITE),
+                Name::new("SelectedTitle"),
+            ));
+            panel.spawn((
+                Text::new("Entity: -"),
+                TextFont { font_size: 14.0, ..default() },
+                TextColor(Color::WHITE),
+                SelectedEntityIdText,
+                Name::new("SelectedEntityIdText"),
+            ));
+            panel.spawn((
+                Text::new("Calories: -/-"),
+                TextFont { font_size: 14.0, ..default() },
+                TextColor(Color::WHITE),
+                SelectedCaloriesText,
+                Name::new("SelectedCaloriesText"),
+            ));
+            panel.spawn((
+                Text::new("Hydration: -/-"),
+                TextFont { font_size: 14.0, ..default() },
+                TextColor(Color::WHITE),
+                SelectedHydrationText,
+                Name::new("SelectedHydrationText"),
+            ));
+            panel.spawn((
+                Text::new("Rest: -/-"),
+                TextFont { font_size: 14.0, ..default() },
+                TextColor(Color::WHITE),
+                SelectedRestText,
+                Name::new("SelectedRestText"),
+            ));
+            panel.spawn((
+                Text::new("Warmth: -/-"),
+                TextFont { font_size: 14.0, ..default() },
+                TextColor(Color::WHITE),
+                SelectedWarmthText,
+                Name::new("SelectedWarmthText"),
+            ));
+            panel.spawn((
+                Text::new("Pregnancy: no"),
+                TextFont { font_size: 14.0, ..default() },
+                TextColor(Color::WHITE),
+                SelectedPregnancyText,
+                Name::new("SelectedPregnancyText"),
+            ));
+            panel.spawn((
+                Text::new("Family: -"),
+                TextFont { font_size: 14.0, ..default() },
+                TextColor(Color::WHITE),
+                SelectedFamilyTreeText,
+                Name::new("SelectedFamilyTreeText"),
+            ));
+            panel.spawn((
+                Text::new("Decision: -"),
+                TextFont { font_size: 14.0, ..default() },
+                TextColor(Color::WHITE),
+                SelectedUtilityText,
+                Name::new("SelectedUtilityText"),
+            ));
+        });
+
+        // Inspector sections for plants, tiles and the band
+        for (section, title, lines) in [
+            (PanelSection::Plant, "Selected Plant", &[
+                (InspectorLine::PlantType, "Type: -"),
+                (InspectorLine::PlantNutrition, "Nutrition: -"),
+                (InspectorLine::PlantStatus, "Status: -"),
+            ][..]),
+            (PanelSection::Tile, "Selected Tile", &[
+                (InspectorLine::TileKind, "Kind: -"),
+                (InspectorLine::TileMoveCost, "Move cost: -"),
+                (InspectorLine::TileFertility, "Fertility: -"),
+                (InspectorLine::TileElevation, "Elevation: -"),
+            ][..]),
+            (PanelSection::Band, "Band", &[
+                (InspectorLine::BandCenter, "Center: -"),
+                (InspectorLine::BandMembers, "Members: -"),
+                (InspectorLine::BandMode, "Mode: -"),
+                (InspectorLine::BandLineages, "Lineages: -"),
+            ][..]),
+        ] {
+            panel.spawn((
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.0),
+                    display: Display::None,
+                    ..default()
+                },
+                section,
+                Name::new(format!("{:?} Section", section)),
+            )).with_children(|panel| {
+                panel.spawn((
+                    Text::new(title),
+                    TextFont { font_size: 18.0, ..default() },
+                    TextColor(Color::WHITE),
+                    Name::new(format!("{:?}Title", section)),
+                ));
+                for &(line, placeholder) in lines {
+                    panel.spawn((
+                        Text::new(placeholder),
+                        TextFont { font_size: 14.0, ..default() },
+                        TextColor(Color::WHITE),
+                        line,
+                        Name::new(format!("{:?}Text", line)),
+                    ));
+                }
+            });
+        }
     });
 
     // Spacer for your game viewport (UI doesn't render here)
@@ -194,44 +387,146 @@ pub fn spawn_ui (
         ))
         .id();
 
+    // Right charts panel (initially hidden); the Interaction keeps its clicks off the map
+    let charts_panel = commands
+        .spawn((
+            Node {
+                width: Val::Px(CHART_WIDTH as f32 + 16.0),
+                height: Val::Percent(100.0),
+                display: Display::None,
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(8.0)),
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.06, 0.06, 0.08)),
+            Interaction::default(),
+            ChartsPanelRoot,
+            Name::new("Charts Panel"),
+        ))
+        .id();
+
+    commands.entity(charts_panel).with_children(|panel| {
+        panel.spawn((
+            Node {
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(4.0),
+                ..default()
+            },
+            Name::new("Chart Window Buttons"),
+        )).with_children(|row| {
+            for window in ChartWindow::ALL {
+                row.spawn((
+                    Button,
+                    Node {
+                        padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                        ..default()
+                    },
+                    BackgroundColor(if window == ChartWindow::default() { CHART_BUTTON_SELECTED_COLOR } else { CHART_BUTTON_COLOR }),
+                    ChartWindowButton(window),
+                    Name::new(format!("{:?} Button", window)),
+                )).with_child((
+                    Text::new(window.label()),
+                    TextFont { font_size: 14.0, ..default() },
+                    TextColor(Color::WHITE),
+                ));
+            }
+        });
+
+        for kind in ChartKind::ALL {
+            panel.spawn((
+                Text::new(kind.title()),
+                TextFont { font_size: 16.0, ..default() },
+                TextColor(Color::WHITE),
+                Name::new(format!("{:?} Chart Title", kind)),
+            ));
+            let image = images.add(Image::new_fill(
+                Extent3d { width: CHART_WIDTH, height: CHART_HEIGHT, depth_or_array_layers: 1 },
+                TextureDimension::D2,
+                &[0, 0, 0, 255],
+                TextureFormat::Rgba8UnormSrgb,
+                RenderAssetUsages::default(),
+            ));
+            panel.spawn((
+                ImageNode::new(image),
+                Node {
+                    width: Val::Px(CHART_WIDTH as f32),
+                    height: Val::Px(CHART_HEIGHT as f32),
+                    ..default()
+                },
+                kind,
+                Name::new(format!("{:?} Chart", kind)),
+            ));
+            // Legend, one coloured span per series
+            panel.spawn((
+                Text::default(),
+                TextFont { font_size: 12.0, ..default() },
+                Name::new(format!("{:?} Chart Legend", kind)),
+            )).with_children(|legend| {
+                for (index, series) in chart_series(kind).iter().enumerate() {
+                    legend.spawn((
+                        TextSpan::new(format!("{}: -  ", series.label)),
+                        TextFont { font_size: 12.0, ..default() },
+                        TextColor(series.color),
+                        ChartLegend { chart: kind, series: index },
+                    ));
+                }
+            });
+        }
+    });
+
+    // --- Event Log (initially hidden; newest first, scrolled with the mouse wheel) ---
+    let event_log_panel = commands
+        .spawn((
+            Node {
+                height: Val::Px(EVENT_LOG_HEIGHT),
+                width: Val::Percent(100.0),
+                display: Display::None,
+                flex_direction: FlexDirection::Column,
+                overflow: Overflow::scroll_y(),
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.06, 0.06, 0.08)),
+            Interaction::default(),
+            ScrollPosition::default(),
+            EventLogPanelRoot,
+            Name::new("Event Log"),
+        ))
+        .with_child((
+            Text::new(""),
+            TextFont { font_size: 12.0, ..default() },
+            TextColor(Color::WHITE),
+            Node { flex_shrink: 0.0, ..default() },
+            EventLogText,
+            Name::new("EventLogText"),
+        ))
+        .id();
+
     // Build hierarchy
-    commands.entity(root).add_children(&[top_bar, main_row]);
+    commands.entity(root).add_children(&[top_bar, main_row, event_log_panel]);
     commands
         .entity(main_row)
-        .add_children(&[left_panel, viewport_spacer]);
+        .add_children(&[left_panel, viewport_spacer, charts_panel]);
 }
 
 pub fn setup_visualization_system(
     mut commands: Commands,
     grid: Res<GameGrid>,
     world_seed: Res<WorldSeed>,
+    world_preset: Res<WorldPreset>,
 ) {
     // --- Draw the Grid ---
     // We spawn a sprite for each tile only once
     for (y, row) in grid.tiles.iter().enumerate() {
         for (x, tile) in row.iter().enumerate() {
-            let (color, image) = match tile.kind {
-                TileKind::Empty => {
-                    if (x + y) % 2 == 0 {
-                        (Color::srgb(0.4, 0.4, 0.4), default())
-                    } else {
-                        (Color::srgb(0.5, 0.5, 0.5), default())
-                    }
-                }
-                TileKind::Dirt => {
-                    (Color::srgb(0.5, 0.5, 0.5), default())
-                }
-                TileKind::Water => {
-                    (Color::srgb(0.0, 0.0, 1.0), default())
-                }
-            };
+            let color = tile_color(tile, x, y);
 
             commands.spawn((
                 TileMarker,
                 Sprite {
                     color,
                     custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
-                    image,
                     ..default()
                 },
                 Transform::from_xyz(
@@ -244,7 +539,29 @@ pub fn setup_visualization_system(
         }
     }
     
+    // Darkens the map (and creatures, which sit below it) at night
+    commands.spawn((
+        NightOverlay,
+        Sprite {
+            color: Color::srgba(0.0, 0.0, 0.05, 0.0),
+            custom_size: Some(Vec2::new(GRID_WIDTH as f32 * TILE_SIZE, GRID_HEIGHT as f32 * TILE_SIZE)),
+            ..default()
+        },
+        Transform::from_xyz(0.0, 0.0, 2.5),
+    ));
+
+    commands.spawn((
+        SelectionBox,
+        Sprite {
+            color: Color::srgba(1.0, 1.0, 1.0, 0.15),
+            ..default()
+        },
+        Transform::from_xyz(0.0, 0.0, 3.0),
+        Visibility::Hidden,
+    ));
+
     info!("World seed: {}", world_seed.0);
+    info!("World preset: {:?}", *world_preset);
 }
 
 // --- Helper Functions ---
@@ -257,34 +574,259 @@ fn generate_seed() -> u32 {
     rng.random_range(0..u32::MAX)
 }
 
-fn generate_height_map(seed: u32) -> Vec<Vec<Tile>> {
+fn generate_height_map(seed: u32, preset: WorldPreset) -> Vec<Vec<Tile>> {
     let perlin = Perlin::new(seed);
-    let mut map = vec![vec![Tile { kind: TileKind::Empty, move_cost: 0 }; GRID_WIDTH]; GRID_HEIGHT];
-    for y in 0..GRID_HEIGHT {
-        for x in 0..GRID_WIDTH {
+    let continents = match preset {
+        WorldPreset::Archipelago => generate_islands(seed),
+        _ => preset.continents(),
+    };
+    let barriers = preset.barriers();
+    let mut map = vec![vec![Tile { kind: TileKind::Empty, move_cost: 0, elevation: 0.0, surface: Surface::Natural, fertility: 0.0 }; GRID_WIDTH]; GRID_HEIGHT];
+    for (y, row) in map.iter_mut().enumerate() {
+        for (x, tile) in row.iter_mut().enumerate() {
             let nx = x as f64 * SCALE;
             let ny = y as f64 * SCALE;
             let raw_height = perlin.get([nx, ny]); // Value in [-1, 1]
-            let height = ((raw_height + 1.0) / 2.0) as f32; // Normalize to [0,1]
-            if height < WATER_LEVEL {
-                map[y][x] = Tile { kind: TileKind::Water, move_cost: 100 };
-            } else {
-                map[y][x] = Tile { kind: TileKind::Dirt, move_cost: 1 };
+            let noise_height = ((raw_height + 1.0) / 2.0) as f32; // Normalize to [0,1]
+            let mut height = noise_height;
+            let mut elevation = noise_height;
+
+            // Shape the noise into continents when the preset defines them
+            let (u, v) = (x as f32 / GRID_WIDTH as f32, y as f32 / GRID_HEIGHT as f32);
+            if !continents.is_empty() {
+                height = continent_height(&continents, u, v, noise_height);
+                elevation = coastal_relief(height, noise_height);
             }
+            let (kind, move_cost) = if height < WATER_LEVEL {
+                (TileKind::Water, 100)
+            } else {
+                match barrier_at(&barriers, u, v, height) {
+                    Some(BarrierKind::Mountain) => (TileKind::Mountain, MOUNTAIN_MOVE_COST),
+                    Some(BarrierKind::Desert) => (TileKind::Desert, DESERT_MOVE_COST),
+                    // High ground is mountainous wherever it rises
+                    None if elevation >= MOUNTAIN_ELEVATION => (TileKind::Mountain, MOUNTAIN_MOVE_COST),
+                    None => (TileKind::Dirt, 1),
+                }
+            };
+            *tile = Tile { kind, move_cost, elevation, surface: Surface::Natural, fertility: 0.0 };
         }
     }
+
+    // --- Hydrology ---
+    mark_inland_lakes(&mut map);
+    generate_rivers(&mut map, seed);
+    mark_floodplains(&mut map);
+    mark_shallows(&mut map);
+    // Soil starts out at its natural fertility
+    for tile in map.iter_mut().flatten() {
+        tile.fertility = tile.kind.natural_fertility();
+    }
     map
 }
 
-fn find_dirt_near_center(grid: &Vec<Vec<Tile>>) -> (Position, Position) {
-    let center_x = (GRID_WIDTH / 2) as i32;
-    let center_y = (GRID_HEIGHT / 2) as i32;
+// Water connected to the map edge is salt ocean; enclosed water bodies are fresh lakes
+fn mark_inland_lakes(map: &mut [Vec<Tile>]) {
+    let mut is_ocean = vec![vec![false; GRID_WIDTH]; GRID_HEIGHT];
+    let mut stack = Vec::new();
+    for (y, row) in map.iter().enumerate() {
+        for (x, tile) in row.iter().enumerate() {
+            let on_edge = x == 0 || y == 0 || x == GRID_WIDTH - 1 || y == GRID_HEIGHT - 1;
+            if on_edge && tile.kind == TileKind::Water {
+                is_ocean[y][x] = true;
+                stack.push((x, y));
+            }
+        }
+    }
+
+    while let Some((x, y)) = stack.pop() {
+        for (nx, ny) in cardinal_neighbors(x, y) {
+            if !is_ocean[ny][nx] && map[ny][nx].kind == TileKind::Water {
+                is_ocean[ny][nx] = true;
+                stack.push((nx, ny));
+            }
+        }
+    }
+
+    for (y, row) in map.iter_mut().enumerate() {
+        for (x, tile) in row.iter_mut().enumerate() {
+            if tile.kind == TileKind::Water && !is_ocean[y][x] {
+                tile.kind = TileKind::Lake;
+            }
+        }
+    }
+}
+
+// Traces rivers downhill from random highland sources until they reach other water.
+// A river that gets stuck in a basin pools into a small lake.
+fn generate_rivers(map: &mut [Vec<Tile>], seed: u32) {
+    // Use a different seed offset so river sources don't follow the terrain noise
+    let river_seed = seed.wrapping_add(67890);
+    let mut rng = Pcg32::new(river_seed as u64, 0);
+
+    let mut rivers_placed = 0;
+    let mut attempts = 0;
+    while rivers_placed < RIVER_COUNT && attempts < RIVER_COUNT * 50 {
+        attempts += 1;
+        let (mut x, mut y) = (rng.random_range(0..GRID_WIDTH), rng.random_range(0..GRID_HEIGHT));
+        if map[y][x].kind.is_water() || map[y][x].elevation < RIVER_SOURCE_HEIGHT {
+            continue;
+        }
+
+        for _ in 0..RIVER_MAX_LENGTH {
+            map[y][x] = Tile { kind: TileKind::River, move_cost: RIVER_MOVE_COST, ..map[y][x] };
+
+            let lowest = cardinal_neighbors(x, y)
+                .min_by(|a, b| map[a.1][a.0].elevation.total_cmp(&map[b.1][b.0].elevation));
+            let Some((nx, ny)) = lowest else { break };
+
+            if map[ny][nx].kind.is_water() {
+                break; // Joined the ocean, a lake or another river
+            }
+            if map[ny][nx].elevation >= map[y][x].elevation {
+                pool_lake(map, x, y);
+                break;
+            }
+            (x, y) = (nx, ny);
+        }
+        rivers_placed += 1;
+    }
+}
+
+fn pool_lake(map: &mut [Vec<Tile>], cx: usize, cy: usize) {
+    let radius = RIVER_BASIN_LAKE_RADIUS as i32;
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            let (x, y) = (cx as i32 + dx, cy as i32 + dy);
+            if dx * dx + dy * dy <= radius * radius
+                && (0..GRID_WIDTH as i32).contains(&x) && (0..GRID_HEIGHT as i32).contains(&y)
+                && !map[y as usize][x as usize].kind.is_water() {
+                map[y as usize][x as usize] = Tile { kind: TileKind::Lake, move_cost: 100, ..map[y as usize][x as usize] };
+            }
+        }
+    }
+}
+
+// Dry land near a river becomes fertile floodplain
+fn mark_floodplains(map: &mut [Vec<Tile>]) {
+    let radius = FLOODPLAIN_RADIUS as i32;
+    let mut floodplain = Vec::new();
+    for (y, row) in map.iter().enumerate() {
+        for (x, tile) in row.iter().enumerate() {
+            if !matches!(tile.kind, TileKind::Dirt | TileKind::Desert) {
+                continue;
+            }
+            let near_river = (-radius..=radius).any(|dy| (-radius..=radius).any(|dx| {
+                let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                (0..GRID_WIDTH as i32).contains(&nx) && (0..GRID_HEIGHT as i32).contains(&ny)
+                    && map[ny as usize][nx as usize].kind == TileKind::River
+            }));
+            if near_river {
+                floodplain.push((x, y));
+            }
+        }
+    }
+    for (x, y) in floodplain {
+        map[y][x] = Tile { kind: TileKind::Floodplain, move_cost: 1, ..map[y][x] };
+    }
+}
+
+// Ocean close to land is shallow enough to swim; the open sea beyond needs boats
+fn mark_shallows(map: &mut [Vec<Tile>]) {
+    let radius = SHALLOW_WATER_RADIUS;
+    let mut shallows = Vec::new();
+    for (y, row) in map.iter().enumerate() {
+        for (x, tile) in row.iter().enumerate() {
+            if tile.kind != TileKind::Water {
+                continue;
+            }
+            let near_land = (-radius..=radius).any(|dy| (-radius..=radius).any(|dx| {
+                let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                (0..GRID_WIDTH as i32).contains(&nx) && (0..GRID_HEIGHT as i32).contains(&ny)
+                    && !map[ny as usize][nx as usize].kind.is_water()
+            }));
+            if near_land {
+                shallows.push((x, y));
+            }
+        }
+    }
+    for (x, y) in shallows {
+        map[y][x] = Tile { kind: TileKind::Shallows, move_cost: SWIM_MOVE_COST, ..map[y][x] };
+    }
+}
+
+fn cardinal_neighbors(x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+    [(0i32, 1i32), (0, -1), (1, 0), (-1, 0)].into_iter().filter_map(move |(dx, dy)| {
+        let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+        if (0..GRID_WIDTH as i32).contains(&nx) && (0..GRID_HEIGHT as i32).contains(&ny) {
+            Some((nx as usize, ny as usize))
+        } else {
+            None
+        }
+    })
+}
+
+// Land is highest at a continent's center and falls below water past its edge;
+// the noise only roughens the coastline.
+fn continent_height(continents: &[Continent], u: f32, v: f32, noise_height: f32) -> f32 {
+    let land = continents.iter()
+        .map(|c| {
+            let dx = (u - c.center.0) / c.radius.0;
+            let dy = (v - c.center.1) / c.radius.1;
+            1.0 - (dx * dx + dy * dy).sqrt()
+        })
+        .fold(f32::MIN, f32::max);
+    (noise_height * CONTINENT_NOISE_WEIGHT + land * 0.8).clamp(0.0, 1.0)
+}
+
+// The continent shape only decides where land is; relief comes from the terrain noise,
+// rising from sea level over the first stretch inland
+fn coastal_relief(height: f32, noise_height: f32) -> f32 {
+    if height < WATER_LEVEL {
+        return height;
+    }
+    let inland = ((height - WATER_LEVEL) / COAST_RISE).min(1.0);
+    WATER_LEVEL + inland * (noise_height - WATER_LEVEL).max(0.0)
+}
+
+// Barriers are bands around a line segment; noise makes their edges irregular
+fn barrier_at(barriers: &[Barrier], u: f32, v: f32, noise_height: f32) -> Option<BarrierKind> {
+    barriers.iter()
+        .find(|b| {
+            let (sx, sy) = b.start;
+            let (ex, ey) = b.end;
+            let (lx, ly) = (ex - sx, ey - sy);
+            let t = (((u - sx) * lx + (v - sy) * ly) / (lx * lx + ly * ly)).clamp(0.0, 1.0);
+            let (px, py) = (sx + t * lx, sy + t * ly);
+            let dist = ((u - px) * (u - px) + (v - py) * (v - py)).sqrt();
+            dist < b.width * (0.5 + noise_height)
+        })
+        .map(|b| b.kind)
+}
+
+fn generate_islands(seed: u32) -> Vec<Continent> {
+    // Use a different seed offset so islands don't follow the terrain noise
+    let island_seed = seed.wrapping_add(54321);
+    let mut rng = Pcg32::new(island_seed as u64, 0);
+    // Always keep an island at the spawn point so the founding band has land
+    let mut islands = vec![Continent { center: (0.5, 0.5), radius: (0.05, 0.08) }];
+    for _ in 1..ARCHIPELAGO_ISLAND_COUNT {
+        islands.push(Continent {
+            center: (rng.random_range(0.05..0.95), rng.random_range(0.05..0.95)),
+            radius: (rng.random_range(0.015..0.06), rng.random_range(0.025..0.1)),
+        });
+    }
+    islands
+}
+
+fn find_dirt_near_spawn(grid: &[Vec<Tile>], spawn_point: (f32, f32)) -> (Position, Position) {
+    let center_x = (spawn_point.0 * GRID_WIDTH as f32) as i32;
+    let center_y = (spawn_point.1 * GRID_HEIGHT as f32) as i32;
     let mut dirt_positions = Vec::new();
     
-    'outer: for radius in 0..20 {
-        for dy in -(radius as i32)..=(radius as i32) {
-            for dx in -(radius as i32)..=(radius as i32) {
-                if radius == 0 || dx.abs() == radius as i32 || dy.abs() == radius as i32 {
+    'outer: for radius in 0i32..100 {
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                if radius == 0 || dx.abs() == radius || dy.abs() == radius {
                     let (x, y) = (center_x + dx, center_y + dy);
                     
                     if (0..GRID_WIDTH as i32).contains(&x) && (0..GRID_HEIGHT as i32).contains(&y) 
@@ -306,30 +848,33 @@ fn find_dirt_near_center(grid: &Vec<Vec<Tile>>) -> (Position, Position) {
     }
 }
 
-fn generate_wheat_patches(commands: &mut Commands, grid_tiles: &Vec<Vec<Tile>>, world_seed: u32) {
+fn generate_wheat_patches(commands: &mut Commands, grid_tiles: &[Vec<Tile>], world_seed: u32) {
     // Use a different seed offset for wheat generation to create different patterns
     let wheat_seed = world_seed.wrapping_add(12345);
     let wheat_noise = Perlin::new(wheat_seed);
     
-    for y in 0..GRID_HEIGHT {
-        for x in 0..GRID_WIDTH {
-            // Only place wheat on dirt tiles
-            if grid_tiles[y][x].kind == TileKind::Dirt {
-                let nx = x as f64 * WHEAT_SCALE;
-                let ny = y as f64 * WHEAT_SCALE;
-                let wheat_noise_value = wheat_noise.get([nx, ny]); // Value in [-1, 1]
-                let normalized_wheat = ((wheat_noise_value + 1.0) / 2.0) as f32; // Normalize to [0,1]
-                
-                // Primary wheat patch determination
-                if normalized_wheat > WHEAT_THRESHOLD {
-                    commands.spawn((
-                        PlantMarker { plant_type: PlantType::Wheat },
-                        Position { x: x as i32, y: y as i32 },
-                        FoodSource { nutrition_value: WHEAT_NUTRIENTS },
-                        Harvestable,
-                        Edible,
-                    ));
-                }
+    for (y, row) in grid_tiles.iter().enumerate() {
+        for (x, tile) in row.iter().enumerate() {
+            // Only place wheat on dirt tiles, and more readily the richer the soil
+            if !matches!(tile.kind, TileKind::Dirt | TileKind::Floodplain) {
+                continue;
+            }
+            let threshold = WHEAT_THRESHOLD - (tile.fertility - DIRT_FERTILITY) * WHEAT_FERTILITY_BONUS;
+
+            let nx = x as f64 * WHEAT_SCALE;
+            let ny = y as f64 * WHEAT_SCALE;
+            let wheat_noise_value = wheat_noise.get([nx, ny]); // Value in [-1, 1]
+            let normalized_wheat = ((wheat_noise_value + 1.0) / 2.0) as f32; // Normalize to [0,1]
+            
+            // Primary wheat patch determination
+            if normalized_wheat > threshold {
+                commands.spawn((
+                    PlantMarker { plant_type: PlantType::Wheat },
+                    Position { x: x as i32, y: y as i32 },
+                    FoodSource { nutrition_value: WHEAT_NUTRIENTS },
+                    Harvestable,
+                    Edible,
+                ));
             }
         }
     }
@@ -1,15 +1,70 @@
 use bevy::prelude::*;
-use crate::resources::game_state::GameState;
+use crate::resources::{
+    game_state::{GameState, SimulationSpeed, SingleStep},
+    replay::{Replay, ReplayInput},
+    ui_elements::TickCount,
+};
 
 pub fn toggle_pause_system(
     keyboard_input: Res<ButtonInput<KeyCode>>,
     mut next_state: ResMut<NextState<GameState>>,
     current_state: Res<State<GameState>>,
+    mut replay: ResMut<Replay>,
+    tick_count: Res<TickCount>,
 ) {
     if keyboard_input.just_pressed(KeyCode::Space) {
         match current_state.get() {
-            GameState::Running => next_state.set(GameState::Paused),
-            GameState::Paused => next_state.set(GameState::Running),
+            GameState::Running => {
+                next_state.set(GameState::Paused);
+                replay.record(tick_count.0, ReplayInput::Pause);
+            }
+            GameState::Paused => {
+                next_state.set(GameState::Running);
+                replay.record(tick_count.0, ReplayInput::Resume);
+            }
         }
     }
 }
+
+// Minus and Equal step the simulation speed down and up through its presets
+pub fn simulation_speed_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut speed: ResMut<SimulationSpeed>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+) {
+    let new_speed = if keyboard_input.just_pressed(KeyCode::Equal) {
+        speed.faster()
+    } else if keyboard_input.just_pressed(KeyCode::Minus) {
+        speed.slower()
+    } else {
+        return;
+    };
+    if new_speed != *speed {
+        *speed = new_speed;
+        fixed_time.set_timestep_hz(speed.tick_rate_hz());
+        info!("Simulation speed: {}", speed.label());
+    }
+}
+
+// Period advances a paused simulation by a single tick
+pub fn single_step_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    current_state: Res<State<GameState>>,
+    mut single_step: ResMut<SingleStep>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Period) && *current_state.get() == GameState::Paused {
+        single_step.0 = true;
+    }
+}
+
+// Run condition letting the tick systems through for a requested single step
+pub fn single_step_requested(single_step: Res<SingleStep>) -> bool {
+    single_step.0
+}
+
+// Ends a single step once its tick has run
+pub fn finish_single_step_system(mut single_step: ResMut<SingleStep>) {
+    if single_step.0 {
+        single_step.0 = false;
+    }
+}
@@ -0,0 +1,170 @@
+use bevy::prelude::*;
+use rand::Rng;
+use crate::components::components::*;
+use crate::resources::{
+    game_grid::{GameGrid, TileKind, Tile, SpatialGrid},
+    calendar::{Calendar, Season},
+    weather::{Weather, WeatherKind},
+    seed::SimRng,
+};
+use crate::constants::*;
+
+
+// Rolls weather and disasters once per day
+#[allow(clippy::too_many_arguments)]
+pub fn weather_system(
+    mut commands: Commands,
+    calendar: Res<Calendar>,
+    mut weather: ResMut<Weather>,
+    mut game_grid: ResMut<GameGrid>,
+    spatial_grid: Res<SpatialGrid>,
+    plant_query: Query<&Position, With<PlantMarker>>,
+    mut tile_events: EventWriter<TileChanged>,
+    mut disaster_events: EventWriter<DisasterOccurred>,
+    mut sim_rng: ResMut<SimRng>,
+) {
+    if !calendar.is_changed() {
+        return;
+    }
+    let rng = &mut sim_rng.0;
+
+    // --- Harsh winters are decided as winter begins ---
+    if calendar.season == Season::Winter && calendar.day == 1 {
+        weather.harsh_winter = rng.random::<f32>() < HARSH_WINTER_CHANCE;
+        if weather.harsh_winter {
+            disaster_events.write(DisasterOccurred { kind: DisasterKind::HarshWinter, position: None });
+        }
+    } else if calendar.season != Season::Winter {
+        weather.harsh_winter = false;
+    }
+
+    // --- Receding flood water ---
+    if weather.flood_days_remaining > 0 {
+        weather.flood_days_remaining -= 1;
+        if weather.flood_days_remaining == 0 {
+            // The water leaves silt behind that restores the soil
+            for (pos, tile) in weather.flooded_tiles.drain(..) {
+                game_grid.tiles[pos.y as usize][pos.x as usize] = Tile { fertility: tile.kind.natural_fertility().max(tile.fertility), ..tile };
+                tile_events.write(TileChanged { position: pos });
+            }
+        }
+    }
+
+    // --- Daily weather ---
+    if weather.days_remaining == 0 {
+        let new_kind = WeatherKind::roll(calendar.season, rng);
+        if new_kind != weather.kind {
+            info!("Weather changed to {:?}", new_kind);
+        }
+        weather.kind = new_kind;
+        weather.days_remaining = rng.random_range(WEATHER_MIN_DAYS..=WEATHER_MAX_DAYS);
+    }
+    weather.days_remaining -= 1;
+
+    // --- Disasters ---
+    match weather.kind {
+        WeatherKind::Rain if weather.flooded_tiles.is_empty() && rng.random::<f32>() < FLOOD_CHANCE => {
+            if let Some(origin) = flood(&mut commands, &mut weather, &mut game_grid, &spatial_grid, &plant_query, &mut tile_events, rng) {
+                disaster_events.write(DisasterOccurred { kind: DisasterKind::Flood, position: Some(origin) });
+            }
+        }
+        WeatherKind::Drought if rng.random::<f32>() < WILDFIRE_CHANCE => {
+            if let Some(origin) = wildfire(&mut commands, &spatial_grid, &plant_query, rng) {
+                disaster_events.write(DisasterOccurred { kind: DisasterKind::Wildfire, position: Some(origin) });
+            }
+        }
+        _ => {}
+    }
+}
+
+// Records disasters in the event log
+pub fn log_disaster_system(
+    mut disaster_events: EventReader<DisasterOccurred>,
+    mut sim_events: EventWriter<SimEvent>,
+) {
+    for event in disaster_events.read() {
+        sim_events.write(SimEvent::Disaster { kind: event.kind, position: event.position });
+    }
+}
+
+// --- Helper Functions ---
+
+// A river bursts its banks: nearby floodplain goes under water and its plants drown
+fn flood(
+    commands: &mut Commands,
+    weather: &mut Weather,
+    game_grid: &mut GameGrid,
+    spatial_grid: &SpatialGrid,
+    plant_query: &Query<&Position, With<PlantMarker>>,
+    tile_events: &mut EventWriter<TileChanged>,
+    rng: &mut impl Rng,
+) -> Option<Position> {
+    let river_tiles: Vec<Position> = game_grid.tiles.iter().enumerate()
+        .flat_map(|(y, row)| row.iter().enumerate()
+            .filter(|(_, tile)| tile.kind == TileKind::River)
+            .map(move |(x, _)| Position { x: x as i32, y: y as i32 }))
+        .collect();
+    if river_tiles.is_empty() {
+        return None;
+    }
+    let origin = river_tiles[rng.random_range(0..river_tiles.len())];
+
+    for dy in -FLOOD_RADIUS..=FLOOD_RADIUS {
+        for dx in -FLOOD_RADIUS..=FLOOD_RADIUS {
+            let pos = Position { x: origin.x + dx, y: origin.y + dy };
+            if dx * dx + dy * dy > FLOOD_RADIUS * FLOOD_RADIUS
+                || pos.x < 0 || pos.x >= GRID_WIDTH as i32 || pos.y < 0 || pos.y >= GRID_HEIGHT as i32 {
+                continue;
+            }
+            let tile = game_grid.tiles[pos.y as usize][pos.x as usize];
+            if tile.kind != TileKind::Floodplain {
+                continue;
+            }
+
+            weather.flooded_tiles.push((pos, tile));
+            game_grid.tiles[pos.y as usize][pos.x as usize] = Tile { kind: TileKind::Lake, move_cost: 100, ..tile };
+            tile_events.write(TileChanged { position: pos });
+            despawn_plants_at(commands, spatial_grid, plant_query, pos);
+        }
+    }
+    weather.flood_days_remaining = FLOOD_DURATION_DAYS;
+    Some(origin)
+}
+
+// Fire starts at a random plant and burns every plant within a radius
+fn wildfire(
+    commands: &mut Commands,
+    spatial_grid: &SpatialGrid,
+    plant_query: &Query<&Position, With<PlantMarker>>,
+    rng: &mut impl Rng,
+) -> Option<Position> {
+    let plant_count = plant_query.iter().count();
+    if plant_count == 0 {
+        return None;
+    }
+    let origin = *plant_query.iter().nth(rng.random_range(0..plant_count))?;
+
+    for dy in -WILDFIRE_RADIUS..=WILDFIRE_RADIUS {
+        for dx in -WILDFIRE_RADIUS..=WILDFIRE_RADIUS {
+            if dx * dx + dy * dy <= WILDFIRE_RADIUS * WILDFIRE_RADIUS {
+                despawn_plants_at(commands, spatial_grid, plant_query, Position { x: origin.x + dx, y: origin.y + dy });
+            }
+        }
+    }
+    Some(origin)
+}
+
+fn despawn_plants_at(
+    commands: &mut Commands,
+    spatial_grid: &SpatialGrid,
+    plant_query: &Query<&Position, With<PlantMarker>>,
+    pos: Position,
+) {
+    if let Some(entities) = spatial_grid.0.get(&pos) {
+        for &entity in entities {
+            if plant_query.get(entity).is_ok() {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}